@@ -1,25 +1,122 @@
+use std::sync::Arc;
+
+use log::warn;
 use rig::agent::Agent;
 use rig::client::{CompletionClient, Nothing};
 use rig::providers::openai::responses_api::ResponsesCompletionModel;
 use rig::providers::{gemini, ollama, openai};
 
-use super::{PdfRead, WebFetch, WebSearch};
+use super::{PdfRead, ResearchQuery, WebCrawl, WebFetch, WebSearch, WebSearchMulti};
+use crate::collectors::domain_policy::DomainPolicy;
+use crate::collectors::http_cache::{CacheMode, HttpCache};
+use crate::collectors::opt_out::OptOutPolicy;
+use crate::collectors::politeness::PolitenessConfig;
+use crate::collectors::retry::RetryPolicy;
+use crate::collectors::search::SearchConfig;
+use crate::collectors::url_policy::UrlPolicy;
+use crate::collectors::web::{FetchContext, HttpConfig, ReqwestClient, SizeLimits};
 
 const PREAMBLE: &str =
     "You are a research assistant that helps users gather and summarize information from the web";
 
+/// Options for the tools given to the agent
+#[derive(Debug, Clone, Default)]
+pub struct AgentOptions {
+    /// How the on-disk HTTP cache is used
+    pub cache_mode: CacheMode,
+    /// What to do with pages whose publisher opted out of automated use
+    pub opt_out_policy: OptOutPolicy,
+    /// Private hosts the web tools may still fetch from, e.g. `localhost:8080`
+    pub allowed_local_hosts: Vec<String>,
+    /// Sites the web tools may or may not use
+    pub domain_policy: DomainPolicy,
+    /// How requests failing with a transient error are retried
+    pub retry_policy: RetryPolicy,
+    /// Which search backend the web_search tool uses
+    pub search: SearchConfig,
+    /// Largest pages and PDFs the web tools read
+    pub size_limits: SizeLimits,
+}
+
+/// Web tools sharing one HTTP client, robots.txt cache and politeness scheduler.
+/// Search backends get a client of their own, which may reach a local SearXNG.
+struct WebTools {
+    fetch: WebFetch,
+    crawl: WebCrawl,
+    search: WebSearch,
+    search_multi: WebSearchMulti,
+    research: ResearchQuery,
+}
+
+fn web_tools(options: &AgentOptions) -> WebTools {
+    let url_policy = url_policy(options);
+    let http_config = HttpConfig {
+        size_limits: options.size_limits,
+        url_policy: Some(url_policy.clone()),
+        ..HttpConfig::default()
+    };
+    let http_client = ReqwestClient::new(&http_config).expect("Failed to create HTTP client");
+    let search_client = ReqwestClient::new(&HttpConfig {
+        url_policy: None,
+        ..http_config
+    })
+    .expect("Failed to create HTTP client");
+    let mut fetch_context = FetchContext::new(http_client, PolitenessConfig::default())
+        .with_opt_out_policy(options.opt_out_policy)
+        .with_url_policy(url_policy)
+        .with_domain_policy(options.domain_policy.clone())
+        .with_retry_policy(options.retry_policy.clone())
+        .with_size_limits(options.size_limits);
+    if options.cache_mode != CacheMode::Disabled {
+        match HttpCache::default_dir() {
+            Some(dir) => {
+                fetch_context =
+                    fetch_context.with_http_cache(HttpCache::new(dir, options.cache_mode));
+            }
+            None => warn!("No cache directory found; the HTTP cache is disabled"),
+        }
+    }
+    let fetch_context = Arc::new(fetch_context);
+
+    let search = WebSearch::new(
+        options
+            .search
+            .provider(search_client, options.retry_policy.clone()),
+    )
+    .with_domain_policy(options.domain_policy.clone());
+
+    WebTools {
+        fetch: WebFetch::new(fetch_context.clone()),
+        crawl: WebCrawl::new(fetch_context.clone()),
+        search_multi: WebSearchMulti::new(search.clone()),
+        research: ResearchQuery::new(search.clone(), fetch_context),
+        search,
+    }
+}
+
+fn url_policy(options: &AgentOptions) -> UrlPolicy {
+    options
+        .allowed_local_hosts
+        .iter()
+        .fold(UrlPolicy::default(), |policy, host| policy.allow_host(host))
+}
+
 /// Create an Ollama-based research agent
-pub fn create_ollama_agent(model: &str) -> Agent<ollama::CompletionModel> {
+pub fn create_ollama_agent(model: &str, options: &AgentOptions) -> Agent<ollama::CompletionModel> {
     let client = ollama::Client::builder()
         .api_key(Nothing)
         .build()
         .expect("Failed to create Ollama client");
+    let web_tools = web_tools(options);
 
     client
         .agent(model)
         .preamble(PREAMBLE)
-        .tool(WebFetch::new())
-        .tool(WebSearch)
+        .tool(web_tools.fetch)
+        .tool(web_tools.crawl)
+        .tool(web_tools.search)
+        .tool(web_tools.search_multi)
+        .tool(web_tools.research)
         .tool(PdfRead)
         .build()
 }
@@ -28,27 +125,40 @@ pub fn create_ollama_agent(model: &str) -> Agent<ollama::CompletionModel> {
 pub fn create_gemini_agent(
     api_key: &str,
     model: &str,
+    options: &AgentOptions,
 ) -> Agent<gemini::completion::CompletionModel> {
     let client = gemini::Client::new(api_key).expect("Failed to create Gemini client");
+    let web_tools = web_tools(options);
 
     client
         .agent(model)
         .preamble(PREAMBLE)
-        .tool(WebFetch::new())
-        .tool(WebSearch)
+        .tool(web_tools.fetch)
+        .tool(web_tools.crawl)
+        .tool(web_tools.search)
+        .tool(web_tools.search_multi)
+        .tool(web_tools.research)
         .tool(PdfRead)
         .build()
 }
 
 /// Create an OpenAI-based research agent
-pub fn create_openai_agent(api_key: &str, model: &str) -> Agent<ResponsesCompletionModel> {
+pub fn create_openai_agent(
+    api_key: &str,
+    model: &str,
+    options: &AgentOptions,
+) -> Agent<ResponsesCompletionModel> {
     let client: rig::client::Client<openai::OpenAIResponsesExt> =
         openai::Client::new(api_key).expect("Failed to create OpenAI client");
+    let web_tools = web_tools(options);
     client
         .agent(model)
         .preamble(PREAMBLE)
-        .tool(WebFetch::new())
-        .tool(WebSearch)
+        .tool(web_tools.fetch)
+        .tool(web_tools.crawl)
+        .tool(web_tools.search)
+        .tool(web_tools.search_multi)
+        .tool(web_tools.research)
         .tool(PdfRead)
         .build()
 }
@@ -92,7 +202,7 @@ mod tests {
     #[tokio::test]
     #[ignore]
     async fn test_ollama_agent_with_web_fetch() {
-        let agent = create_ollama_agent("qwen3");
+        let agent = create_ollama_agent("qwen3", &AgentOptions::default());
         let response = agent
             .prompt("Fetch https://example.com and **summarize** it shortly")
             .await
@@ -108,7 +218,11 @@ mod tests {
         dotenv().ok();
 
         let api_key = std::env::var("GEMINI_API_KEY").expect("GEMINI_API_KEY required");
-        let agent = create_gemini_agent(&api_key, gemini::completion::GEMINI_2_5_FLASH);
+        let agent = create_gemini_agent(
+            &api_key,
+            gemini::completion::GEMINI_2_5_FLASH,
+            &AgentOptions::default(),
+        );
         let response = agent
             .prompt("Fetch https://example.com and **summarize** it shortly")
             .await
@@ -124,7 +238,11 @@ mod tests {
         dotenv().ok();
 
         let api_key = std::env::var("OPENAI_API_KEY").expect("OPENAI_API_KEY required");
-        let agent = create_openai_agent(&api_key, openai::completion::GPT_4_1_MINI);
+        let agent = create_openai_agent(
+            &api_key,
+            openai::completion::GPT_4_1_MINI,
+            &AgentOptions::default(),
+        );
         let response = agent
             .prompt("Fetch https://example.com and **summarize** it shortly")
             .await
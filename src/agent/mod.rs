@@ -1,9 +1,17 @@
 mod builder;
 mod pdf_read;
+mod research_query;
+mod web_crawl;
 mod web_fetch;
 mod web_search;
+mod web_search_multi;
 
-pub use builder::{create_gemini_agent, create_ollama_agent, create_openai_agent, default_model};
+pub use builder::{
+    create_gemini_agent, create_ollama_agent, create_openai_agent, default_model, AgentOptions,
+};
 pub use pdf_read::PdfRead;
+pub use research_query::ResearchQuery;
+pub use web_crawl::WebCrawl;
 pub use web_fetch::WebFetch;
 pub use web_search::{WebSearch, WebSearchArgs};
+pub use web_search_multi::WebSearchMulti;
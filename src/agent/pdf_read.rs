@@ -3,12 +3,19 @@ use rig::completion::ToolDefinition;
 use serde::{Deserialize, Serialize};
 use serde_json::json;
 
-use crate::collectors::pdf::read_pdf;
+use crate::collectors::passages::{paged_excerpt, Passage};
+use crate::collectors::pdf::{format_pages, read_pdf, PageRanges};
 
 /// Arguments for the PdfRead tool
 #[derive(Deserialize)]
 pub struct PdfReadArgs {
     file_path: String,
+    /// Pages to read, e.g. "5-12" (all pages if not given)
+    pages: Option<PageRanges>,
+    /// Return only the passages relevant to this query
+    query: Option<String>,
+    max_chars: Option<usize>,
+    max_tokens: Option<usize>,
 }
 
 /// Output from the PdfRead tool
@@ -16,7 +23,16 @@ pub struct PdfReadArgs {
 pub struct PdfReadOutput {
     path: String,
     title: Option<String>,
+    /// Number of pages of the whole document
+    page_count: usize,
+    /// Text of the pages read, each after a `--- Page N ---` marker
     content: String,
+    /// Passages relevant to the query, in document order, instead of `content`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    passages: Option<Vec<Passage>>,
+    /// Whether `content` (or the passage returned) is only a beginning, cut to the budget
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    truncated: bool,
 }
 
 /// Error type for PdfRead tool
@@ -40,13 +56,29 @@ impl rig::tool::Tool for PdfRead {
     async fn definition(&self, _prompt: String) -> ToolDefinition {
         ToolDefinition {
             name: self.name(),
-            description: "Reads a local PDF file and extracts its text content".to_string(),
+            description: "Reads a local PDF file and extracts its text content, page by page, with page markers to cite".to_string(),
             parameters: json!({
                 "type": "object",
                 "properties": {
                     "file_path": {
                         "type": "string",
                         "description": "The local file path to the PDF"
+                    },
+                    "pages": {
+                        "type": "string",
+                        "description": "Pages to read, e.g. \"5-12\", \"3\" or \"1-2,10-\" (pages are numbered from 1). Defaults to all pages; the output gives the page count"
+                    },
+                    "query": {
+                        "type": "string",
+                        "description": "Return only the passages of the document most relevant to this query (with their character offsets) instead of the whole text"
+                    },
+                    "max_chars": {
+                        "type": "integer",
+                        "description": "Maximum number of characters of content or passages to return"
+                    },
+                    "max_tokens": {
+                        "type": "integer",
+                        "description": "Maximum number of tokens of content or passages to return (estimated at 4 characters per token)"
                     }
                 },
                 "required": ["file_path"]
@@ -57,11 +89,23 @@ impl rig::tool::Tool for PdfRead {
     async fn call(&self, args: Self::Args) -> Result<Self::Output, Self::Error> {
         info!("Reading PDF {} ...", args.file_path);
 
-        let pdf_content = tokio::task::spawn_blocking(move || read_pdf(&args.file_path)).await??;
+        let file_path = args.file_path.clone();
+        let pdf_content = tokio::task::spawn_blocking(move || read_pdf(&file_path)).await??;
+        let pages = pdf_content.select_pages(args.pages.as_ref())?;
+        let excerpt = paged_excerpt(
+            format_pages(&pages),
+            &pages,
+            args.query.as_deref(),
+            args.max_chars,
+            args.max_tokens,
+        );
         Ok(PdfReadOutput {
-            path: pdf_content.path,
-            title: pdf_content.title,
-            content: pdf_content.text,
+            page_count: pdf_content.pages.len(),
+            path: pdf_content.path.clone(),
+            title: pdf_content.title.clone(),
+            content: excerpt.content,
+            passages: excerpt.passages,
+            truncated: excerpt.truncated,
         })
     }
 }
@@ -83,7 +127,10 @@ mod tests {
         let output = PdfReadOutput {
             path: "/tmp/test.pdf".to_string(),
             title: Some("Test PDF".to_string()),
+            page_count: 1,
             content: "Hello PDF".to_string(),
+            passages: None,
+            truncated: false,
         };
         let json = serde_json::to_string(&output).unwrap();
         assert!(json.contains("Test PDF"));
@@ -93,9 +140,81 @@ mod tests {
     async fn test_pdf_read_call_file_not_found() {
         let args = PdfReadArgs {
             file_path: "/nonexistent/path/to/file.pdf".to_string(),
+            pages: None,
+            query: None,
+            max_chars: None,
+            max_tokens: None,
         };
         let sut = PdfRead;
         let result = sut.call(args).await;
         assert!(result.is_err());
     }
+
+    fn args(json: &str) -> PdfReadArgs {
+        serde_json::from_str(json).unwrap()
+    }
+
+    #[test]
+    fn test_pdf_read_args_deserialize_pages() {
+        let args = args(r#"{"file_path": "/tmp/test.pdf", "pages": "5-12"}"#);
+        let pages = args.pages.unwrap();
+        assert!(pages.contains(5) && pages.contains(12) && !pages.contains(13));
+
+        assert!(serde_json::from_str::<PdfReadArgs>(
+            r#"{"file_path": "/tmp/test.pdf", "pages": "twelve"}"#
+        )
+        .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_pdf_read_marks_pages() {
+        let output = PdfRead
+            .call(args(r#"{"file_path": "tests/fixtures/pdf/sample.pdf"}"#))
+            .await
+            .unwrap();
+
+        assert!(output.page_count > 1);
+        assert!(output.content.starts_with("--- Page 1 ---\n"));
+        assert!(output
+            .content
+            .contains(&format!("--- Page {} ---", output.page_count)));
+    }
+
+    #[tokio::test]
+    async fn test_pdf_read_selected_pages() {
+        let output = PdfRead
+            .call(args(
+                r#"{"file_path": "tests/fixtures/pdf/sample.pdf", "pages": "2-"}"#,
+            ))
+            .await
+            .unwrap();
+
+        assert!(!output.content.contains("--- Page 1 ---"));
+        assert!(output.content.starts_with("--- Page 2 ---\n"));
+        assert!(output
+            .content
+            .contains("The last page holds the conclusion."));
+
+        let result = PdfRead
+            .call(args(
+                r#"{"file_path": "tests/fixtures/pdf/sample.pdf", "pages": "400-"}"#,
+            ))
+            .await;
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_pdf_read_passages_cite_pages() {
+        let output = PdfRead
+            .call(args(
+                r#"{"file_path": "tests/fixtures/pdf/sample.pdf", "query": "conclusion"}"#,
+            ))
+            .await
+            .unwrap();
+
+        let passages = output.passages.unwrap();
+        assert!(!passages.is_empty());
+        assert_eq!(passages[0].page, Some(output.page_count as u32));
+        assert!(passages[0].text.contains("conclusion"));
+    }
 }
@@ -0,0 +1,321 @@
+use std::sync::Arc;
+
+use futures::future::join_all;
+use log::info;
+use rig::completion::ToolDefinition;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use super::web_search::{WebSearch, WebSearchError};
+use crate::collectors::passages::{select_paged_passages, select_passages, Passage};
+use crate::collectors::pdf::number_pages;
+use crate::collectors::search::{SearchOptions, SearchResult};
+use crate::collectors::web::{fetch_url, ContentFormat, FetchContext};
+
+/// Upper bound on the number of results read by one call
+const MAX_PAGES: usize = 5;
+
+/// Maximum number of characters of passages returned per page
+const MAX_PAGE_CHARS: usize = 2000;
+
+/// Arguments for the ResearchQuery tool
+#[derive(Deserialize)]
+pub struct ResearchQueryArgs {
+    pub query: String,
+    #[serde(flatten)]
+    pub options: SearchOptions,
+}
+
+/// A search result read by the ResearchQuery tool
+#[derive(Serialize)]
+pub struct ResearchSource {
+    url: String,
+    title: String,
+    /// Passages of the page most relevant to the query, in page order
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    passages: Vec<Passage>,
+    /// Why the page could not be read; `passages` is then empty
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+    /// Whether the page was too large to read whole, or its only passage was cut
+    /// to fit the budget
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    truncated: bool,
+}
+
+/// Output from the ResearchQuery tool
+#[derive(Serialize)]
+pub struct ResearchQueryOutput {
+    sources: Vec<ResearchSource>,
+}
+
+/// Error type for ResearchQuery tool
+#[derive(Debug, thiserror::Error)]
+pub enum ResearchQueryError {
+    #[error(transparent)]
+    SearchError(#[from] WebSearchError),
+}
+
+/// Searches the web, then reads the top results concurrently and returns the
+/// passages relevant to the query
+pub struct ResearchQuery {
+    search: WebSearch,
+    context: Arc<FetchContext>,
+}
+
+impl ResearchQuery {
+    /// Create the tool on top of the search tool and a shared fetch context, so reads
+    /// go through the same robots.txt cache, politeness scheduler and policies
+    pub fn new(search: WebSearch, context: Arc<FetchContext>) -> Self {
+        Self { search, context }
+    }
+
+    async fn read(&self, result: SearchResult, query: &str) -> ResearchSource {
+        match fetch_url(&self.context, &result.url, ContentFormat::Text).await {
+            Ok(page) => {
+                let (passages, cut) = if page.pages.is_empty() {
+                    select_passages(&page.text, query, MAX_PAGE_CHARS)
+                } else {
+                    select_paged_passages(&number_pages(&page.pages), query, MAX_PAGE_CHARS)
+                };
+                // The page's final URL, which citations must point at after redirects
+                ResearchSource {
+                    passages,
+                    url: page.url,
+                    title: page.title.unwrap_or(result.title),
+                    error: None,
+                    truncated: page.truncated || cut,
+                }
+            }
+            Err(e) => ResearchSource {
+                url: result.url,
+                title: result.title,
+                passages: Vec::new(),
+                error: Some(e.to_string()),
+                truncated: false,
+            },
+        }
+    }
+}
+
+impl rig::tool::Tool for ResearchQuery {
+    const NAME: &'static str = "research_query";
+    type Error = ResearchQueryError;
+    type Args = ResearchQueryArgs;
+    type Output = ResearchQueryOutput;
+
+    async fn definition(&self, _prompt: String) -> ToolDefinition {
+        let mut properties = WebSearch::options_schema();
+        properties["max_results"] = json!({
+            "type": "integer",
+            "minimum": 1,
+            "maximum": MAX_PAGES,
+            "description": format!("Number of top results to read (default and at most {})", MAX_PAGES)
+        });
+        properties["query"] = json!({
+            "type": "string",
+            "description": "The search query, also used to pick the relevant passages of each page"
+        });
+        ToolDefinition {
+            name: self.name(),
+            description: "Searches the web and reads the top results at once, returning the passages of each page relevant to the query with its URL. Prefer it over web_search followed by several web_fetch calls".to_string(),
+            parameters: json!({
+                "type": "object",
+                "properties": properties,
+                "required": ["query"]
+            }),
+        }
+    }
+
+    async fn call(&self, args: Self::Args) -> Result<Self::Output, Self::Error> {
+        info!("Researching: {} ...", args.query);
+        let options = SearchOptions {
+            max_results: args.options.max_results.min(MAX_PAGES),
+            ..args.options
+        };
+        let results = self.search.search(&args.query, &options).await?;
+
+        let sources = join_all(
+            results
+                .into_iter()
+                .map(|result| self.read(result, &args.query)),
+        )
+        .await;
+
+        Ok(ResearchQueryOutput { sources })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::collectors::politeness::PolitenessConfig;
+    use crate::collectors::search::{SearchError, SearchProvider};
+    use crate::collectors::url_policy::UrlPolicy;
+    use crate::collectors::web::{HttpConfig, ReqwestClient};
+    use futures::future::BoxFuture;
+    use rig::tool::Tool;
+    use std::time::Duration;
+    use wiremock::matchers::{method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    /// Provider returning the same URLs for every query
+    struct StubProvider(Vec<String>);
+
+    impl SearchProvider for StubProvider {
+        fn name(&self) -> &'static str {
+            "stub"
+        }
+
+        fn search<'a>(
+            &'a self,
+            _query: &'a str,
+            options: &'a SearchOptions,
+        ) -> BoxFuture<'a, Result<Vec<SearchResult>, SearchError>> {
+            let results = self
+                .0
+                .iter()
+                .take(options.max_results)
+                .map(|url| SearchResult {
+                    title: format!("Result for {}", url),
+                    url: url.clone(),
+                    snippet: String::new(),
+                })
+                .collect();
+            Box::pin(async move { Ok(results) })
+        }
+    }
+
+    fn research_query(urls: Vec<String>) -> ResearchQuery {
+        let client = ReqwestClient::new(&HttpConfig::default()).unwrap();
+        let politeness = PolitenessConfig {
+            min_interval: Duration::ZERO,
+            ..PolitenessConfig::default()
+        };
+        let context = FetchContext::new(client, politeness)
+            .with_url_policy(UrlPolicy::default().allow_host("127.0.0.1"));
+        ResearchQuery::new(
+            WebSearch::new(Arc::new(StubProvider(urls))),
+            Arc::new(context),
+        )
+    }
+
+    fn args(query: &str) -> ResearchQueryArgs {
+        ResearchQueryArgs {
+            query: query.to_string(),
+            options: SearchOptions::default(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_reads_results_and_reports_failures_inline() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/robots.txt"))
+            .respond_with(ResponseTemplate::new(404))
+            .mount(&server)
+            .await;
+        let article = format!(
+            "<html><head><title>Tokio</title></head><body><p>{}</p><p>Tokio is an asynchronous runtime for Rust.</p><p>{}</p></body></html>",
+            "Unrelated gardening notes. ".repeat(40),
+            "Unrelated cooking notes. ".repeat(40),
+        );
+        Mock::given(method("GET"))
+            .and(path("/tokio"))
+            .respond_with(ResponseTemplate::new(200).set_body_raw(article, "text/html"))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/gone"))
+            .respond_with(ResponseTemplate::new(404))
+            .mount(&server)
+            .await;
+        let tool = research_query(vec![
+            format!("{}/tokio", server.uri()),
+            format!("{}/gone", server.uri()),
+        ]);
+
+        let output = tool.call(args("tokio runtime")).await.unwrap();
+
+        assert_eq!(output.sources.len(), 2);
+        let read = &output.sources[0];
+        assert_eq!(read.title, "Tokio");
+        assert!(read.error.is_none());
+        assert_eq!(read.passages.len(), 1);
+        assert!(read.passages[0]
+            .text
+            .contains("Tokio is an asynchronous runtime for Rust."));
+        let failed = &output.sources[1];
+        assert_eq!(failed.title, format!("Result for {}/gone", server.uri()));
+        assert!(failed.passages.is_empty());
+        assert!(failed.error.as_deref().unwrap().contains("404"));
+    }
+
+    #[tokio::test]
+    async fn test_sources_carry_final_url_after_redirects() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/robots.txt"))
+            .respond_with(ResponseTemplate::new(404))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/old"))
+            .respond_with(ResponseTemplate::new(301).insert_header("location", "/new"))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/new"))
+            .respond_with(ResponseTemplate::new(200).set_body_raw(
+                "<html><body><p>Tokio is an asynchronous runtime for Rust.</p></body></html>",
+                "text/html",
+            ))
+            .mount(&server)
+            .await;
+        let tool = research_query(vec![format!("{}/old", server.uri())]);
+
+        let output = tool.call(args("tokio runtime")).await.unwrap();
+
+        assert_eq!(output.sources[0].url, format!("{}/new", server.uri()));
+        assert!(!output.sources[0].truncated);
+    }
+
+    #[tokio::test]
+    async fn test_pdf_passages_cite_pages() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/robots.txt"))
+            .respond_with(ResponseTemplate::new(404))
+            .mount(&server)
+            .await;
+        let pdf = std::fs::read("tests/fixtures/pdf/sample.pdf").unwrap();
+        Mock::given(method("GET"))
+            .and(path("/report.pdf"))
+            .respond_with(ResponseTemplate::new(200).set_body_raw(pdf, "application/pdf"))
+            .mount(&server)
+            .await;
+        let tool = research_query(vec![format!("{}/report.pdf", server.uri())]);
+
+        let output = tool.call(args("conclusion")).await.unwrap();
+
+        let passages = &output.sources[0].passages;
+        assert!(!passages.is_empty());
+        assert!(passages[0].page.is_some_and(|page| page > 1));
+        assert!(passages[0].text.contains("conclusion"));
+    }
+
+    #[tokio::test]
+    async fn test_reads_at_most_max_pages() {
+        // Answers 404 to everything
+        let server = MockServer::start().await;
+        let urls = (0..10).map(|i| format!("{}/{}", server.uri(), i)).collect();
+        let tool = research_query(urls);
+        let mut args = args("anything");
+        args.options.max_results = 10;
+
+        let output = tool.call(args).await.unwrap();
+
+        assert_eq!(output.sources.len(), MAX_PAGES);
+        assert!(output.sources.iter().all(|source| source.error.is_some()));
+    }
+}
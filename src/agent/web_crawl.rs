@@ -0,0 +1,251 @@
+use std::sync::Arc;
+
+use log::info;
+use rig::completion::ToolDefinition;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use crate::collectors::crawler::{crawl, CrawlFailure, CrawlOptions, CrawlScope};
+use crate::collectors::web::{ContentFormat, FetchContext, FetchError};
+
+/// Upper bound on the crawl depth the model may request
+const MAX_DEPTH: usize = 4;
+
+/// Upper bound on the number of pages the model may request
+const MAX_PAGES: usize = 30;
+
+/// Maximum number of characters of content returned per page
+const MAX_PAGE_CHARS: usize = 4000;
+
+fn default_max_depth() -> usize {
+    2
+}
+
+fn default_max_pages() -> usize {
+    10
+}
+
+/// Arguments for the WebCrawl tool
+#[derive(Deserialize)]
+pub struct WebCrawlArgs {
+    url: String,
+    #[serde(default = "default_max_depth")]
+    max_depth: usize,
+    #[serde(default = "default_max_pages")]
+    max_pages: usize,
+    #[serde(default)]
+    scope: CrawlScope,
+    #[serde(default)]
+    format: ContentFormat,
+}
+
+/// A page in the WebCrawl output
+#[derive(Serialize)]
+pub struct WebCrawlPage {
+    url: String,
+    depth: usize,
+    title: Option<String>,
+    content: String,
+    /// Whether `content` was cut at `MAX_PAGE_CHARS` or the page at the size limit
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    truncated: bool,
+}
+
+/// Output from the WebCrawl tool
+#[derive(Serialize)]
+pub struct WebCrawlOutput {
+    pages: Vec<WebCrawlPage>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    failures: Vec<CrawlFailure>,
+}
+
+/// Error type for WebCrawl tool
+#[derive(Debug, thiserror::Error)]
+pub enum WebCrawlError {
+    #[error("Failed to crawl: {0}")]
+    CrawlError(#[from] FetchError),
+}
+
+pub struct WebCrawl {
+    context: Arc<FetchContext>,
+}
+
+impl WebCrawl {
+    /// Create the tool on top of a shared fetch context
+    pub fn new(context: Arc<FetchContext>) -> Self {
+        Self { context }
+    }
+}
+
+impl rig::tool::Tool for WebCrawl {
+    const NAME: &'static str = "web_crawl";
+    type Error = WebCrawlError;
+    type Args = WebCrawlArgs;
+    type Output = WebCrawlOutput;
+
+    async fn definition(&self, _prompt: String) -> ToolDefinition {
+        ToolDefinition {
+            name: self.name(),
+            description: "Crawls a section of a website breadth-first from a start URL and returns the title and text of each page read. Use it to read a whole documentation section at once".to_string(),
+            parameters: json!({
+                "type": "object",
+                "properties": {
+                    "url": {
+                        "type": "string",
+                        "description": "The URL to start crawling from"
+                    },
+                    "max_depth": {
+                        "type": "integer",
+                        "description": format!("How many links away from the start URL to go (0 reads only the start page). Defaults to 2, at most {}", MAX_DEPTH)
+                    },
+                    "max_pages": {
+                        "type": "integer",
+                        "description": format!("Maximum number of pages to read. Defaults to 10, at most {}", MAX_PAGES)
+                    },
+                    "scope": {
+                        "type": "string",
+                        "enum": ["path_prefix", "same_origin"],
+                        "description": "\"path_prefix\" (default) follows only links under the start URL's directory (e.g. /docs/), \"same_origin\" follows any link on the same site"
+                    },
+                    "format": {
+                        "type": "string",
+                        "enum": ["text", "markdown"],
+                        "description": "Output format: \"text\" for plain paragraphs (default), \"markdown\" to keep headings, lists, tables, code blocks and links"
+                    }
+                },
+                "required": ["url"]
+            }),
+        }
+    }
+
+    async fn call(&self, args: Self::Args) -> Result<Self::Output, Self::Error> {
+        info!("Crawling {} ...", args.url);
+        let options = CrawlOptions {
+            max_depth: args.max_depth.min(MAX_DEPTH),
+            max_pages: args.max_pages.clamp(1, MAX_PAGES),
+            scope: args.scope,
+            format: args.format,
+        };
+        let result = crawl(&self.context, &args.url, &options).await?;
+
+        Ok(WebCrawlOutput {
+            pages: result
+                .pages
+                .into_iter()
+                .map(|page| {
+                    let (content, truncated) = truncate_chars(page.text, MAX_PAGE_CHARS);
+                    WebCrawlPage {
+                        url: page.url,
+                        depth: page.depth,
+                        title: page.title,
+                        content,
+                        truncated: truncated || page.truncated,
+                    }
+                })
+                .collect(),
+            failures: result.failures,
+        })
+    }
+}
+
+/// Cut `text` to at most `max_chars` characters
+fn truncate_chars(mut text: String, max_chars: usize) -> (String, bool) {
+    match text.char_indices().nth(max_chars) {
+        Some((end, _)) => {
+            text.truncate(end);
+            (text, true)
+        }
+        None => (text, false),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::collectors::politeness::PolitenessConfig;
+    use crate::collectors::url_policy::UrlPolicy;
+    use crate::collectors::web::{HttpConfig, ReqwestClient};
+    use rig::tool::Tool;
+    use std::time::Duration;
+    use wiremock::matchers::{method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    fn web_crawl() -> WebCrawl {
+        let client = ReqwestClient::new(&HttpConfig::default()).unwrap();
+        let politeness = PolitenessConfig {
+            min_interval: Duration::ZERO,
+            ..PolitenessConfig::default()
+        };
+        let context = FetchContext::new(client, politeness)
+            .with_url_policy(UrlPolicy::default().allow_host("127.0.0.1"));
+        WebCrawl::new(Arc::new(context))
+    }
+
+    #[test]
+    fn test_web_crawl_args_defaults() {
+        let json = r#"{"url": "https://example.com/docs/"}"#;
+        let args: WebCrawlArgs = serde_json::from_str(json).unwrap();
+        assert_eq!(args.max_depth, 2);
+        assert_eq!(args.max_pages, 10);
+        assert_eq!(args.scope, CrawlScope::PathPrefix);
+        assert_eq!(args.format, ContentFormat::Text);
+    }
+
+    #[test]
+    fn test_web_crawl_args_deserialize_scope() {
+        let json = r#"{"url": "https://example.com", "scope": "same_origin", "max_depth": 1}"#;
+        let args: WebCrawlArgs = serde_json::from_str(json).unwrap();
+        assert_eq!(args.scope, CrawlScope::SameOrigin);
+        assert_eq!(args.max_depth, 1);
+    }
+
+    #[test]
+    fn test_truncate_chars() {
+        assert_eq!(
+            truncate_chars("日本語です".to_string(), 3),
+            ("日本語".to_string(), true)
+        );
+        assert_eq!(
+            truncate_chars("short".to_string(), 10),
+            ("short".to_string(), false)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_web_crawl_tool_reads_stand_in_site() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/docs/"))
+            .respond_with(ResponseTemplate::new(200).set_body_raw(
+                r#"<html><head><title>Docs</title></head><body>
+                   <p>Welcome to the documentation.</p><nav><a href="setup">Setup</a></nav>
+                   </body></html>"#,
+                "text/html",
+            ))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/docs/setup"))
+            .respond_with(ResponseTemplate::new(200).set_body_raw(
+                "<html><head><title>Setup</title></head><body><p>Install it.</p></body></html>",
+                "text/html",
+            ))
+            .mount(&server)
+            .await;
+        let json = format!(r#"{{"url": "{}/docs/"}}"#, server.uri());
+        let args: WebCrawlArgs = serde_json::from_str(&json).unwrap();
+
+        let output = web_crawl().call(args).await.unwrap();
+
+        let value = serde_json::to_value(&output).unwrap();
+        assert_eq!(value["pages"][0]["title"], "Docs");
+        assert_eq!(
+            value["pages"][0]["content"],
+            "Welcome to the documentation."
+        );
+        assert_eq!(value["pages"][1]["title"], "Setup");
+        assert_eq!(value["pages"][1]["depth"], 1);
+        assert!(value["pages"][1].get("truncated").is_none());
+        assert!(value.get("failures").is_none());
+    }
+}
@@ -1,15 +1,28 @@
+use std::sync::Arc;
+
 use log::info;
 use rig::completion::ToolDefinition;
 use serde::{Deserialize, Serialize};
 use serde_json::json;
 
-use crate::collectors::robots::RobotsCache;
-use crate::collectors::web::fetch_url;
+use crate::collectors::links::Link;
+use crate::collectors::metadata::PageMetadata;
+use crate::collectors::passages::{excerpt, paged_excerpt, Passage};
+use crate::collectors::pdf::number_pages;
+use crate::collectors::web::{fetch_url, ContentFormat, FetchContext, FetchError};
 
 /// Arguments for the WebFetch tool
 #[derive(Deserialize)]
 pub struct WebFetchArgs {
     url: String,
+    #[serde(default)]
+    format: ContentFormat,
+    #[serde(default)]
+    include_links: bool,
+    /// Return only the passages relevant to this query
+    query: Option<String>,
+    max_chars: Option<usize>,
+    max_tokens: Option<usize>,
 }
 
 /// Output from the WebFetch tool
@@ -17,30 +30,37 @@ pub struct WebFetchArgs {
 pub struct WebFetchOutput {
     title: Option<String>,
     content: String,
+    /// Passages relevant to the query, in page order, instead of `content`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    passages: Option<Vec<Passage>>,
+    #[serde(skip_serializing_if = "PageMetadata::is_empty")]
+    metadata: PageMetadata,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    links: Option<Vec<Link>>,
+    /// Whether `content` (or the passage returned) is only the beginning, cut to the
+    /// requested budget or because the page was too large to read whole
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    truncated: bool,
 }
 
+/// Maximum number of links returned to the model
+const MAX_LINKS: usize = 200;
+
 /// Error type for WebFetch tool
 #[derive(Debug, thiserror::Error)]
 pub enum WebFetchError {
     #[error("Failed to fetch URL: {0}")]
-    FetchError(#[from] anyhow::Error),
+    FetchError(#[from] FetchError),
 }
 
 pub struct WebFetch {
-    robots_cache: RobotsCache,
-}
-
-impl Default for WebFetch {
-    fn default() -> Self {
-        Self::new()
-    }
+    context: Arc<FetchContext>,
 }
 
 impl WebFetch {
-    pub fn new() -> Self {
-        Self {
-            robots_cache: RobotsCache::new(),
-        }
+    /// Create the tool on top of a shared fetch context
+    pub fn new(context: Arc<FetchContext>) -> Self {
+        Self { context }
     }
 }
 
@@ -53,13 +73,34 @@ impl rig::tool::Tool for WebFetch {
     async fn definition(&self, _prompt: String) -> ToolDefinition {
         ToolDefinition {
             name: self.name(),
-            description: "Fetches content from a web URL".to_string(),
+            description: "Fetches content from a web URL (HTML page or PDF document)".to_string(),
             parameters: json!({
                 "type": "object",
                 "properties": {
                     "url": {
                         "type": "string",
                         "description": "The URL to fetch"
+                    },
+                    "format": {
+                        "type": "string",
+                        "enum": ["text", "markdown"],
+                        "description": "Output format: \"text\" for plain paragraphs (default), \"markdown\" to keep headings, lists, tables, code blocks and links"
+                    },
+                    "include_links": {
+                        "type": "boolean",
+                        "description": "Also return the links found on the page (absolute URL and anchor text), to choose which page to open next. Defaults to false"
+                    },
+                    "query": {
+                        "type": "string",
+                        "description": "Return only the passages of the page most relevant to this query (with their character offsets) instead of the whole content"
+                    },
+                    "max_chars": {
+                        "type": "integer",
+                        "description": "Maximum number of characters of content or passages to return"
+                    },
+                    "max_tokens": {
+                        "type": "integer",
+                        "description": "Maximum number of tokens of content or passages to return (estimated at 4 characters per token)"
                     }
                 },
                 "required": ["url"]
@@ -69,10 +110,34 @@ impl rig::tool::Tool for WebFetch {
 
     async fn call(&self, args: Self::Args) -> Result<Self::Output, Self::Error> {
         info!("Fetching {} ...", args.url);
-        let page = fetch_url(&args.url, &self.robots_cache).await?;
+        let page = fetch_url(&self.context, &args.url, args.format).await?;
+        // PDFs keep their pages, so that passages can be cited by page
+        let pages = number_pages(&page.pages);
+        let excerpt = if pages.is_empty() {
+            excerpt(
+                page.text,
+                args.query.as_deref(),
+                args.max_chars,
+                args.max_tokens,
+            )
+        } else {
+            paged_excerpt(
+                page.text,
+                &pages,
+                args.query.as_deref(),
+                args.max_chars,
+                args.max_tokens,
+            )
+        };
         Ok(WebFetchOutput {
             title: page.title,
-            content: page.text,
+            content: excerpt.content,
+            passages: excerpt.passages,
+            metadata: page.metadata,
+            links: args
+                .include_links
+                .then(|| page.links.into_iter().take(MAX_LINKS).collect()),
+            truncated: page.truncated || excerpt.truncated,
         })
     }
 }
@@ -80,13 +145,59 @@ impl rig::tool::Tool for WebFetch {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::collectors::politeness::PolitenessConfig;
+    use crate::collectors::url_policy::UrlPolicy;
+    use crate::collectors::web::{HttpConfig, ReqwestClient};
     use rig::tool::Tool;
+    use wiremock::matchers::{method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    fn web_fetch() -> WebFetch {
+        let client = ReqwestClient::new(&HttpConfig::default()).unwrap();
+        WebFetch::new(Arc::new(FetchContext::new(
+            client,
+            PolitenessConfig::default(),
+        )))
+    }
 
     #[test]
     fn test_web_fetch_args_deserialize() {
         let json = r#"{"url": "https://example.com"}"#;
         let args: WebFetchArgs = serde_json::from_str(json).unwrap();
         assert_eq!(args.url, "https://example.com");
+        assert_eq!(args.format, ContentFormat::Text);
+    }
+
+    #[test]
+    fn test_web_fetch_args_deserialize_markdown_format() {
+        let json = r#"{"url": "https://example.com", "format": "markdown"}"#;
+        let args: WebFetchArgs = serde_json::from_str(json).unwrap();
+        assert_eq!(args.format, ContentFormat::Markdown);
+    }
+
+    #[test]
+    fn test_web_fetch_args_deserialize_include_links() {
+        let json = r#"{"url": "https://example.com", "include_links": true}"#;
+        let args: WebFetchArgs = serde_json::from_str(json).unwrap();
+        assert!(args.include_links);
+    }
+
+    #[test]
+    fn test_web_fetch_output_serialize_links() {
+        let output = WebFetchOutput {
+            title: None,
+            content: "Index".to_string(),
+            passages: None,
+            metadata: PageMetadata::default(),
+            links: Some(vec![Link {
+                url: "https://example.com/a".to_string(),
+                text: "A".to_string(),
+            }]),
+            truncated: false,
+        };
+        let value = serde_json::to_value(&output).unwrap();
+        assert_eq!(value["links"][0]["url"], "https://example.com/a");
+        assert_eq!(value["links"][0]["text"], "A");
     }
 
     #[test]
@@ -94,15 +205,130 @@ mod tests {
         let output = WebFetchOutput {
             title: Some("Test".to_string()),
             content: "Hello".to_string(),
+            passages: None,
+            metadata: PageMetadata::default(),
+            links: None,
+            truncated: false,
         };
         let json = serde_json::to_string(&output).unwrap();
         assert!(json.contains("Test"));
+        assert!(!json.contains("metadata"));
+        assert!(!json.contains("links"));
+        assert!(!json.contains("truncated"));
+    }
+
+    #[test]
+    fn test_web_fetch_output_serialize_truncated() {
+        let output = WebFetchOutput {
+            title: None,
+            content: "The beginning".to_string(),
+            passages: None,
+            metadata: PageMetadata::default(),
+            links: None,
+            truncated: true,
+        };
+        let value = serde_json::to_value(&output).unwrap();
+        assert_eq!(value["truncated"], true);
+    }
+
+    #[test]
+    fn test_web_fetch_output_serialize_metadata() {
+        let output = WebFetchOutput {
+            title: None,
+            content: "Hello".to_string(),
+            passages: None,
+            metadata: PageMetadata {
+                author: Some("Jane".to_string()),
+                published_time: Some("2025-01-01".to_string()),
+                ..PageMetadata::default()
+            },
+            links: None,
+            truncated: false,
+        };
+        let value = serde_json::to_value(&output).unwrap();
+        assert_eq!(value["metadata"]["author"], "Jane");
+        assert_eq!(value["metadata"]["published_time"], "2025-01-01");
+        assert!(value["metadata"].get("description").is_none());
+    }
+
+    #[test]
+    fn test_web_fetch_args_deserialize_budget() {
+        let json = r#"{"url": "https://example.com", "query": "pricing", "max_tokens": 500}"#;
+        let args: WebFetchArgs = serde_json::from_str(json).unwrap();
+        assert_eq!(args.query.as_deref(), Some("pricing"));
+        assert_eq!(args.max_chars, None);
+        assert_eq!(args.max_tokens, Some(500));
+    }
+
+    #[tokio::test]
+    async fn test_web_fetch_returns_passages_for_query() {
+        let server = MockServer::start().await;
+        let page = format!(
+            "<html><body><p>{}</p><p>Our pricing starts at 10 euros per month.</p><p>{}</p></body></html>",
+            "We love open source. ".repeat(50),
+            "Contact us by email. ".repeat(50),
+        );
+        Mock::given(method("GET"))
+            .and(path("/about"))
+            .respond_with(ResponseTemplate::new(200).set_body_raw(page, "text/html"))
+            .mount(&server)
+            .await;
+        let client = ReqwestClient::new(&HttpConfig::default()).unwrap();
+        let context = FetchContext::new(client, PolitenessConfig::default())
+            .with_url_policy(UrlPolicy::default().allow_host("127.0.0.1"));
+        let sut = WebFetch::new(Arc::new(context));
+        let json = format!(
+            r#"{{"url": "{}/about", "query": "pricing per month", "max_chars": 800}}"#,
+            server.uri()
+        );
+
+        let output = sut
+            .call(serde_json::from_str(&json).unwrap())
+            .await
+            .unwrap();
+
+        assert!(output.content.is_empty());
+        let passages = output.passages.unwrap();
+        assert_eq!(passages.len(), 1);
+        assert!(passages[0]
+            .text
+            .contains("Our pricing starts at 10 euros per month."));
+        assert!(passages[0].text.chars().count() <= 800);
+    }
+
+    #[tokio::test]
+    async fn test_web_fetch_pdf_passages_cite_pages() {
+        let pdf = std::fs::read("tests/fixtures/pdf/sample.pdf").unwrap();
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/report.pdf"))
+            .respond_with(ResponseTemplate::new(200).set_body_raw(pdf, "application/pdf"))
+            .mount(&server)
+            .await;
+        let client = ReqwestClient::new(&HttpConfig::default()).unwrap();
+        let context = FetchContext::new(client, PolitenessConfig::default())
+            .with_url_policy(UrlPolicy::default().allow_host("127.0.0.1"));
+        let sut = WebFetch::new(Arc::new(context));
+        let json = format!(
+            r#"{{"url": "{}/report.pdf", "query": "conclusion"}}"#,
+            server.uri()
+        );
+
+        let output = sut
+            .call(serde_json::from_str(&json).unwrap())
+            .await
+            .unwrap();
+
+        let passages = output.passages.unwrap();
+        assert!(!passages.is_empty());
+        assert!(passages[0].page.is_some_and(|page| page > 1));
+        assert!(passages[0].text.contains("conclusion"));
     }
 
     #[tokio::test]
     #[ignore]
     async fn test_web_fetch_tool_get_example_url() {
-        let sut = WebFetch::new();
+        let sut = web_fetch();
         let json = r#"{"url": "https://example.com"}"#;
         let args: WebFetchArgs = serde_json::from_str(json).unwrap();
 
@@ -115,7 +341,7 @@ mod tests {
     #[tokio::test]
     #[ignore]
     async fn test_web_fetch_tool_fail_to_get_web_page() {
-        let sut = WebFetch::new();
+        let sut = web_fetch();
         let json = r#"{"url": "https://lobalhost"}"#;
         let args: WebFetchArgs = serde_json::from_str(json).unwrap();
 
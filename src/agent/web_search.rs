@@ -1,22 +1,20 @@
-use dotenvy::dotenv;
-use log::{error, info};
+use log::{debug, error, info};
 use rig::completion::ToolDefinition;
 use serde::{Deserialize, Serialize};
 use serde_json::json;
-use std::env;
+use std::sync::Arc;
+
+use crate::collectors::domain_policy::DomainPolicy;
+use crate::collectors::search::{
+    SearchError, SearchOptions, SearchProvider, SearchResult, MAX_RESULTS,
+};
 
 /// Arguments for the WebSearch tool
 #[derive(Deserialize)]
 pub struct WebSearchArgs {
     pub query: String,
-}
-
-/// A single search result
-#[derive(Serialize, Debug, Clone)]
-pub struct SearchResult {
-    pub title: String,
-    pub url: String,
-    pub snippet: String,
+    #[serde(flatten)]
+    pub options: SearchOptions,
 }
 
 /// Output from the WebSearch tool
@@ -28,14 +26,96 @@ pub struct WebSearchOutput {
 /// Error type for WebSearch tool
 #[derive(Debug, thiserror::Error)]
 pub enum WebSearchError {
-    #[error("API key not found: set TAVILY_API_KEY environment variable")]
-    ApiKeyNotFound,
     #[error("Search failed: {0}")]
-    SearchError(String),
+    SearchError(#[from] SearchError),
 }
 
-/// WebSearch tool for searching the web using Tavily API
-pub struct WebSearch;
+/// WebSearch tool for searching the web through a configurable provider
+#[derive(Clone)]
+pub struct WebSearch {
+    provider: Arc<dyn SearchProvider>,
+    domain_policy: DomainPolicy,
+}
+
+impl WebSearch {
+    /// Create the tool on top of a search provider
+    pub fn new(provider: Arc<dyn SearchProvider>) -> Self {
+        Self {
+            provider,
+            domain_policy: DomainPolicy::default(),
+        }
+    }
+
+    /// Leave out results from sites the domain policy keeps the agent off
+    pub fn with_domain_policy(mut self, policy: DomainPolicy) -> Self {
+        self.domain_policy = policy;
+        self
+    }
+
+    /// JSON schema of the search options, shared by the search tools
+    pub(crate) fn options_schema() -> serde_json::Value {
+        json!({
+            "max_results": {
+                "type": "integer",
+                "minimum": 1,
+                "maximum": MAX_RESULTS,
+                "description": "Maximum number of results (default 5)"
+            },
+            "time_range": {
+                "type": "string",
+                "enum": ["day", "week", "month", "year"],
+                "description": "Only return results published within this period"
+            },
+            "include_domains": {
+                "type": "array",
+                "items": { "type": "string" },
+                "description": "Only return results from these domains, e.g. [\"docs.rs\"]"
+            },
+            "exclude_domains": {
+                "type": "array",
+                "items": { "type": "string" },
+                "description": "Never return results from these domains"
+            },
+            "topic": {
+                "type": "string",
+                "enum": ["general", "news"],
+                "description": "Use \"news\" for current events (default \"general\")"
+            },
+            "search_depth": {
+                "type": "string",
+                "enum": ["basic", "advanced"],
+                "description": "\"advanced\" is slower but finds more relevant snippets (default \"basic\")"
+            }
+        })
+    }
+
+    /// Run one search and drop the results the domain policy keeps the agent off
+    pub(crate) async fn search(
+        &self,
+        query: &str,
+        options: &SearchOptions,
+    ) -> Result<Vec<SearchResult>, WebSearchError> {
+        options.validate()?;
+        let results = self
+            .provider
+            .search(query, options)
+            .await
+            .inspect_err(|e| error!("{} search failed: {}", self.provider.name(), e))?;
+        Ok(self.allowed_results(results))
+    }
+
+    fn allowed_results(&self, mut results: Vec<SearchResult>) -> Vec<SearchResult> {
+        let before = results.len();
+        results.retain(|result| self.domain_policy.allows(&result.url));
+        if results.len() < before {
+            debug!(
+                "Dropped {} search results blocked by the domain policy",
+                before - results.len()
+            );
+        }
+        results
+    }
+}
 
 impl rig::tool::Tool for WebSearch {
     const NAME: &'static str = "web_search";
@@ -44,17 +124,17 @@ impl rig::tool::Tool for WebSearch {
     type Output = WebSearchOutput;
 
     async fn definition(&self, _prompt: String) -> ToolDefinition {
+        let mut properties = Self::options_schema();
+        properties["query"] = json!({
+            "type": "string",
+            "description": "The search query"
+        });
         ToolDefinition {
             name: self.name(),
             description: "Searches the web for information using a query".to_string(),
             parameters: json!({
                 "type": "object",
-                "properties": {
-                    "query": {
-                        "type": "string",
-                        "description": "The search query"
-                    }
-                },
+                "properties": properties,
                 "required": ["query"]
             }),
         }
@@ -63,42 +143,9 @@ impl rig::tool::Tool for WebSearch {
     async fn call(&self, args: Self::Args) -> Result<Self::Output, Self::Error> {
         info!("Searching for: {} ...", args.query);
 
-        dotenv().ok();
-        let tavily_api_key = match env::var("TAVILY_API_KEY") {
-            Err(_e) => return Err(WebSearchError::ApiKeyNotFound),
-            Ok(k) => k,
-        };
-
-        let client = reqwest::Client::new();
-        const TAVILY_RESEARCH_URL: &str = "https://api.tavily.com/search";
-        let body = json!(
-            {
-                "api_key": tavily_api_key,
-                "query": args.query,
-            }
-        );
-        let response = client.post(TAVILY_RESEARCH_URL).json(&body).send().await;
-
-        let response_body = match response {
-            Ok(r) => r.text().await.unwrap(),
-            Err(e) => {
-                error!("{}", e);
-                return Err(WebSearchError::SearchError(e.to_string()));
-            }
-        };
-        let parsed_response: serde_json::Value = serde_json::from_str(&response_body).unwrap();
-        let contents: Vec<SearchResult> = parsed_response["results"]
-            .as_array()
-            .unwrap_or(&vec![])
-            .iter()
-            .map(|item| SearchResult {
-                title: item["title"].as_str().unwrap_or("").to_string(),
-                url: item["url"].as_str().unwrap_or("").to_string(),
-                snippet: item["content"].as_str().unwrap_or("").to_string(),
-            })
-            .collect();
-
-        Ok(WebSearchOutput { results: contents })
+        Ok(WebSearchOutput {
+            results: self.search(&args.query, &args.options).await?,
+        })
     }
 }
 
@@ -107,12 +154,69 @@ mod tests {
     use rig::tool::Tool;
 
     use super::*;
-    #[test]
+    use crate::collectors::retry::RetryPolicy;
+    use crate::collectors::search::{SearchConfig, SearchDepth, TimeRange, Topic};
+    use crate::collectors::web::{HttpConfig, ReqwestClient};
+    use futures::future::BoxFuture;
+
+    /// Provider returning the same results for every query
+    struct StubProvider(Vec<SearchResult>);
+
+    impl SearchProvider for StubProvider {
+        fn name(&self) -> &'static str {
+            "stub"
+        }
+
+        fn search<'a>(
+            &'a self,
+            _query: &'a str,
+            options: &'a SearchOptions,
+        ) -> BoxFuture<'a, Result<Vec<SearchResult>, SearchError>> {
+            let results = self.0.iter().take(options.max_results).cloned().collect();
+            Box::pin(async move { Ok(results) })
+        }
+    }
 
+    #[test]
     fn test_web_search_args_deserialize() {
         const JSON_ARGS: &str = r#"{"query": "How is the weather in Tokyo today?"}"#;
         let args: WebSearchArgs = serde_json::from_str(JSON_ARGS).unwrap();
         assert_eq!(args.query, "How is the weather in Tokyo today?");
+        assert_eq!(args.options, SearchOptions::default());
+    }
+
+    #[test]
+    fn test_web_search_args_deserialize_options() {
+        const JSON_ARGS: &str = r#"{
+            "query": "tokio release",
+            "max_results": 10,
+            "time_range": "month",
+            "include_domains": ["tokio.rs"],
+            "topic": "news",
+            "search_depth": "advanced"
+        }"#;
+        let args: WebSearchArgs = serde_json::from_str(JSON_ARGS).unwrap();
+
+        assert_eq!(args.query, "tokio release");
+        assert_eq!(args.options.max_results, 10);
+        assert_eq!(args.options.time_range, Some(TimeRange::Month));
+        assert_eq!(args.options.include_domains, vec!["tokio.rs"]);
+        assert_eq!(args.options.topic, Topic::News);
+        assert_eq!(args.options.search_depth, SearchDepth::Advanced);
+    }
+
+    #[tokio::test]
+    async fn test_invalid_options_are_rejected() {
+        let tool = WebSearch::new(Arc::new(StubProvider(Vec::new())));
+        let args: WebSearchArgs =
+            serde_json::from_str(r#"{"query": "tokio", "max_results": 100}"#).unwrap();
+
+        let result = tool.call(args).await;
+
+        assert!(matches!(
+            result,
+            Err(WebSearchError::SearchError(SearchError::InvalidOptions(_)))
+        ));
     }
 
     #[test]
@@ -145,13 +249,44 @@ mod tests {
         assert_eq!(value["results"][1]["snippet"], "This is a example page2");
     }
 
+    #[tokio::test]
+    async fn test_results_blocked_by_domain_policy_are_dropped() {
+        let result = |url: &str| SearchResult {
+            title: "Title".to_string(),
+            url: url.to_string(),
+            snippet: "Snippet".to_string(),
+        };
+        let policy = DomainPolicy {
+            deny: vec!["*.paywalled.example".parse().unwrap()],
+            ..DomainPolicy::default()
+        };
+        let provider = StubProvider(vec![
+            result("https://news.paywalled.example/story"),
+            result("https://example.com/story"),
+        ]);
+        let tool = WebSearch::new(Arc::new(provider)).with_domain_policy(policy);
+
+        let output = tool
+            .call(WebSearchArgs {
+                query: "story".to_string(),
+                options: SearchOptions::default(),
+            })
+            .await
+            .unwrap();
+
+        assert_eq!(output.results.len(), 1);
+        assert_eq!(output.results[0].url, "https://example.com/story");
+    }
+
     #[tokio::test]
     #[ignore]
     async fn test_call_web_search_tool() {
         const JSON_ARGS: &str = r#"{"query": "How is the weather in Tokyo today?"}"#;
         let args: WebSearchArgs = serde_json::from_str(JSON_ARGS).unwrap();
 
-        let tool = WebSearch {};
+        dotenvy::dotenv().ok();
+        let client = ReqwestClient::new(&HttpConfig::default()).unwrap();
+        let tool = WebSearch::new(SearchConfig::Tavily.provider(client, RetryPolicy::default()));
         let response = tool.call(args).await.unwrap();
         assert!(!response.results[0].title.is_empty());
         assert!(!response.results[0].url.is_empty());
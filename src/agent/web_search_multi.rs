@@ -0,0 +1,293 @@
+use futures::future::join_all;
+use log::info;
+use rig::completion::ToolDefinition;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use super::web_search::{WebSearch, WebSearchError};
+use crate::collectors::search::{fuse, FusedResult, SearchOptions};
+
+/// Upper bound on the number of queries run by one call
+const MAX_QUERIES: usize = 5;
+
+/// Arguments for the WebSearchMulti tool
+#[derive(Deserialize)]
+pub struct WebSearchMultiArgs {
+    pub queries: Vec<String>,
+    #[serde(flatten)]
+    pub options: SearchOptions,
+}
+
+/// A query that failed while the others succeeded
+#[derive(Serialize)]
+pub struct FailedQuery {
+    pub query: String,
+    pub error: String,
+}
+
+/// Output from the WebSearchMulti tool
+#[derive(Serialize)]
+pub struct WebSearchMultiOutput {
+    /// Results of all queries, deduplicated and best first
+    pub results: Vec<FusedResult>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub failures: Vec<FailedQuery>,
+}
+
+/// Error type for WebSearchMulti tool
+#[derive(Debug, thiserror::Error)]
+pub enum WebSearchMultiError {
+    #[error("Give between 1 and {MAX_QUERIES} distinct queries")]
+    InvalidQueries,
+    #[error(transparent)]
+    SearchError(#[from] WebSearchError),
+}
+
+/// Runs several phrasings of a search concurrently and merges their results with
+/// reciprocal rank fusion
+pub struct WebSearchMulti {
+    search: WebSearch,
+}
+
+impl WebSearchMulti {
+    /// Create the tool on top of the single-query search tool, sharing its provider
+    /// and domain policy
+    pub fn new(search: WebSearch) -> Self {
+        Self { search }
+    }
+}
+
+/// Trimmed queries without blanks and repeats (ignoring case)
+fn distinct_queries(queries: Vec<String>) -> Vec<String> {
+    let mut distinct: Vec<String> = Vec::new();
+    for query in queries {
+        let query = query.trim();
+        if !query.is_empty() && !distinct.iter().any(|q| q.eq_ignore_ascii_case(query)) {
+            distinct.push(query.to_string());
+        }
+    }
+    distinct
+}
+
+impl rig::tool::Tool for WebSearchMulti {
+    const NAME: &'static str = "web_search_multi";
+    type Error = WebSearchMultiError;
+    type Args = WebSearchMultiArgs;
+    type Output = WebSearchMultiOutput;
+
+    async fn definition(&self, _prompt: String) -> ToolDefinition {
+        let mut properties = WebSearch::options_schema();
+        properties["max_results"]["description"] =
+            json!("Maximum number of results per query (default 5)");
+        properties["queries"] = json!({
+            "type": "array",
+            "items": { "type": "string" },
+            "minItems": 1,
+            "maxItems": MAX_QUERIES,
+            "description": "Different phrasings of the search, e.g. synonyms or narrower and broader forms"
+        });
+        ToolDefinition {
+            name: self.name(),
+            description: format!("Runs up to {} search queries at once and returns their results merged, without duplicates and best first, each with the queries that found it. Prefer it over several web_search calls", MAX_QUERIES),
+            parameters: json!({
+                "type": "object",
+                "properties": properties,
+                "required": ["queries"]
+            }),
+        }
+    }
+
+    async fn call(&self, args: Self::Args) -> Result<Self::Output, Self::Error> {
+        let queries = distinct_queries(args.queries);
+        if queries.is_empty() || queries.len() > MAX_QUERIES {
+            return Err(WebSearchMultiError::InvalidQueries);
+        }
+        args.options
+            .validate()
+            .map_err(|e| WebSearchMultiError::SearchError(e.into()))?;
+        info!("Searching for: {} ...", queries.join(" | "));
+
+        let outcomes = join_all(
+            queries
+                .iter()
+                .map(|query| self.search.search(query, &args.options)),
+        )
+        .await;
+
+        let mut ranked = Vec::new();
+        let mut failures = Vec::new();
+        let mut first_error = None;
+        for (query, outcome) in queries.into_iter().zip(outcomes) {
+            match outcome {
+                Ok(results) => ranked.push((query, results)),
+                Err(e) => {
+                    failures.push(FailedQuery {
+                        query,
+                        error: e.to_string(),
+                    });
+                    first_error.get_or_insert(e);
+                }
+            }
+        }
+        // When every query failed, most likely for the same reason, fail as a whole
+        if ranked.is_empty() {
+            if let Some(e) = first_error {
+                return Err(e.into());
+            }
+        }
+
+        Ok(WebSearchMultiOutput {
+            results: fuse(&ranked),
+            failures,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::collectors::domain_policy::DomainPolicy;
+    use crate::collectors::search::{SearchError, SearchProvider, SearchResult};
+    use futures::future::BoxFuture;
+    use reqwest::StatusCode;
+    use rig::tool::Tool;
+    use std::sync::Arc;
+
+    /// Provider answering each query from a fixed table; unknown queries fail
+    struct TableProvider(Vec<(&'static str, Vec<&'static str>)>);
+
+    impl SearchProvider for TableProvider {
+        fn name(&self) -> &'static str {
+            "table"
+        }
+
+        fn search<'a>(
+            &'a self,
+            query: &'a str,
+            options: &'a SearchOptions,
+        ) -> BoxFuture<'a, Result<Vec<SearchResult>, SearchError>> {
+            let results = self
+                .0
+                .iter()
+                .find(|(q, _)| *q == query)
+                .map(|(_, urls)| {
+                    urls.iter()
+                        .take(options.max_results)
+                        .map(|url| SearchResult {
+                            title: url.to_string(),
+                            url: url.to_string(),
+                            snippet: String::new(),
+                        })
+                        .collect()
+                })
+                .ok_or(SearchError::ServerError(StatusCode::BAD_GATEWAY));
+            Box::pin(async move { results })
+        }
+    }
+
+    fn web_search_multi(policy: DomainPolicy) -> WebSearchMulti {
+        let provider = TableProvider(vec![
+            (
+                "tokio runtime",
+                vec!["https://tokio.rs/", "https://docs.rs/tokio"],
+            ),
+            (
+                "rust async runtime",
+                vec![
+                    "https://async.rs/",
+                    "https://tokio.rs/?utm_source=ddg",
+                    "https://spam.example/tokio",
+                ],
+            ),
+        ]);
+        WebSearchMulti::new(WebSearch::new(Arc::new(provider)).with_domain_policy(policy))
+    }
+
+    fn args(queries: &[&str]) -> WebSearchMultiArgs {
+        WebSearchMultiArgs {
+            queries: queries.iter().map(|q| q.to_string()).collect(),
+            options: SearchOptions::default(),
+        }
+    }
+
+    #[test]
+    fn test_web_search_multi_args_deserialize() {
+        let args: WebSearchMultiArgs = serde_json::from_str(
+            r#"{"queries": ["tokio runtime", "rust async runtime"], "time_range": "year"}"#,
+        )
+        .unwrap();
+
+        assert_eq!(args.queries.len(), 2);
+        assert!(args.options.time_range.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_results_are_fused_across_queries() {
+        let policy = DomainPolicy {
+            deny: vec!["spam.example".parse().unwrap()],
+            ..DomainPolicy::default()
+        };
+        let tool = web_search_multi(policy);
+
+        let output = tool
+            .call(args(&[
+                "tokio runtime",
+                "rust async runtime",
+                "Tokio runtime ",
+            ]))
+            .await
+            .unwrap();
+
+        let urls: Vec<_> = output
+            .results
+            .iter()
+            .map(|r| r.result.url.as_str())
+            .collect();
+        assert_eq!(
+            urls,
+            vec![
+                "https://tokio.rs/",
+                "https://async.rs/",
+                "https://docs.rs/tokio"
+            ]
+        );
+        assert_eq!(
+            output.results[0].queries,
+            vec!["tokio runtime", "rust async runtime"]
+        );
+        assert!(output.failures.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_failed_queries_are_reported() {
+        let tool = web_search_multi(DomainPolicy::default());
+
+        let output = tool
+            .call(args(&["tokio runtime", "unknown query"]))
+            .await
+            .unwrap();
+
+        assert_eq!(output.results.len(), 2);
+        assert_eq!(output.failures.len(), 1);
+        assert_eq!(output.failures[0].query, "unknown query");
+
+        let result = tool.call(args(&["unknown query"])).await;
+        assert!(matches!(
+            result,
+            Err(WebSearchMultiError::SearchError(
+                WebSearchError::SearchError(SearchError::ServerError(_))
+            ))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_invalid_queries_are_rejected() {
+        let tool = web_search_multi(DomainPolicy::default());
+
+        let result = tool.call(args(&["", "  "])).await;
+        assert!(matches!(result, Err(WebSearchMultiError::InvalidQueries)));
+
+        let result = tool.call(args(&["a", "b", "c", "d", "e", "f"])).await;
+        assert!(matches!(result, Err(WebSearchMultiError::InvalidQueries)));
+    }
+}
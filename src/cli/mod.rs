@@ -2,8 +2,15 @@ mod history;
 mod render;
 mod repl;
 
+use std::path::PathBuf;
+
 use clap::Parser;
 
+use crate::collectors::domain_policy::{DomainPolicy, DomainRule};
+use crate::collectors::http_cache::CacheMode;
+use crate::collectors::opt_out::OptOutPolicy;
+use crate::collectors::retry::RetryPolicy;
+
 pub use history::{ConversationHistory, DEFAULT_MAX_TURNS};
 
 pub use render::render_markdown;
@@ -19,4 +26,153 @@ pub struct Cli {
 
     /// Query prompt (required if not in interactive mode)
     pub prompt: Option<String>,
+
+    /// Don't read or write the on-disk HTTP cache
+    #[arg(long)]
+    pub no_cache: bool,
+
+    /// Download pages again instead of using the on-disk HTTP cache (new responses are still cached)
+    #[arg(long, conflicts_with = "no_cache")]
+    pub refresh: bool,
+
+    /// What to do with pages that opt out of automated use via X-Robots-Tag or
+    /// meta robots (noai, noindex): enforce, warn or ignore
+    #[arg(long, value_name = "POLICY", default_value = "enforce")]
+    pub opt_out: OptOutPolicy,
+
+    /// Allow fetching from a private or loopback host, e.g. localhost:8080 (repeatable; for local development)
+    #[arg(long = "allow-local-host", value_name = "HOST")]
+    pub allowed_local_hosts: Vec<String>,
+
+    /// Config file (default: $XDG_CONFIG_HOME/cipherant/config.toml)
+    #[arg(long, value_name = "PATH")]
+    pub config: Option<PathBuf>,
+
+    /// Only use these sites, e.g. docs.rs, *.rust-lang.org or example.com/docs/ (repeatable; replaces the config file's allowlist)
+    #[arg(long = "allow-domain", value_name = "RULE")]
+    pub allowed_domains: Vec<DomainRule>,
+
+    /// Never use these sites (repeatable; replaces the config file's denylist)
+    #[arg(long = "deny-domain", value_name = "RULE")]
+    pub denied_domains: Vec<DomainRule>,
+
+    /// Attempts per web request on timeouts, 429 and 502/503/504 responses (1 disables retries)
+    #[arg(long, value_name = "N", default_value_t = 3, value_parser = clap::value_parser!(u32).range(1..=10))]
+    pub max_attempts: u32,
+}
+
+impl Cli {
+    /// How the on-disk HTTP cache should be used
+    pub fn cache_mode(&self) -> CacheMode {
+        if self.no_cache {
+            CacheMode::Disabled
+        } else if self.refresh {
+            CacheMode::Refresh
+        } else {
+            CacheMode::Normal
+        }
+    }
+
+    /// How web requests are retried
+    pub fn retry_policy(&self) -> RetryPolicy {
+        RetryPolicy {
+            max_attempts: self.max_attempts,
+            ..RetryPolicy::default()
+        }
+    }
+
+    /// The config file's domain policy with the lists given on the command line
+    /// in place of the config file's
+    pub fn domain_policy(&self, mut policy: DomainPolicy) -> DomainPolicy {
+        if !self.allowed_domains.is_empty() {
+            policy.allow = self.allowed_domains.clone();
+        }
+        if !self.denied_domains.is_empty() {
+            policy.deny = self.denied_domains.clone();
+        }
+        policy
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cache_mode_from_flags() {
+        let mode = |args: &[&str]| Cli::parse_from(args).cache_mode();
+
+        assert_eq!(mode(&["cipherant", "query"]), CacheMode::Normal);
+        assert_eq!(
+            mode(&["cipherant", "--refresh", "query"]),
+            CacheMode::Refresh
+        );
+        assert_eq!(
+            mode(&["cipherant", "--no-cache", "-i"]),
+            CacheMode::Disabled
+        );
+    }
+
+    #[test]
+    fn test_opt_out_policy_flag() {
+        let policy = |args: &[&str]| Cli::parse_from(args).opt_out;
+
+        assert_eq!(policy(&["cipherant", "query"]), OptOutPolicy::Enforce);
+        assert_eq!(
+            policy(&["cipherant", "--opt-out", "warn", "query"]),
+            OptOutPolicy::Warn
+        );
+        assert!(Cli::try_parse_from(["cipherant", "--opt-out", "never", "q"]).is_err());
+    }
+
+    #[test]
+    fn test_allow_local_host_is_repeatable() {
+        let cli = Cli::parse_from([
+            "cipherant",
+            "--allow-local-host",
+            "localhost:8080",
+            "--allow-local-host",
+            "127.0.0.1",
+            "query",
+        ]);
+
+        assert_eq!(cli.allowed_local_hosts, vec!["localhost:8080", "127.0.0.1"]);
+    }
+
+    #[test]
+    fn test_domain_flags_replace_config_lists() {
+        let config = DomainPolicy {
+            allow: vec!["docs.rs".parse().unwrap()],
+            deny: vec!["*.medium.com".parse().unwrap()],
+        };
+        let cli = Cli::parse_from(["cipherant", "--allow-domain", "*.rust-lang.org", "query"]);
+
+        let policy = cli.domain_policy(config.clone());
+
+        assert_eq!(policy.allow, vec!["*.rust-lang.org".parse().unwrap()]);
+        assert_eq!(policy.deny, config.deny);
+        assert!(Cli::try_parse_from(["cipherant", "--deny-domain", "*", "q"]).is_err());
+    }
+
+    #[test]
+    fn test_max_attempts_flag() {
+        assert_eq!(
+            Cli::parse_from(["cipherant", "q"])
+                .retry_policy()
+                .max_attempts,
+            3
+        );
+        assert_eq!(
+            Cli::parse_from(["cipherant", "--max-attempts", "1", "q"])
+                .retry_policy()
+                .max_attempts,
+            1
+        );
+        assert!(Cli::try_parse_from(["cipherant", "--max-attempts", "0", "q"]).is_err());
+    }
+
+    #[test]
+    fn test_no_cache_conflicts_with_refresh() {
+        assert!(Cli::try_parse_from(["cipherant", "--no-cache", "--refresh", "q"]).is_err());
+    }
 }
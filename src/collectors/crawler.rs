@@ -0,0 +1,525 @@
+use std::collections::{HashSet, VecDeque};
+
+use log::{debug, info};
+use reqwest::Url;
+use serde::{Deserialize, Serialize};
+
+use super::web::{fetch_url, ContentFormat, FetchContext, FetchError, HttpClient};
+
+/// File extensions of links that are never worth fetching during a crawl
+const SKIPPED_EXTENSIONS: &[&str] = &[
+    "png", "jpg", "jpeg", "gif", "svg", "webp", "ico", "css", "js", "zip", "gz", "tar", "mp3",
+    "mp4", "webm", "woff", "woff2",
+];
+
+/// Which links a crawl may follow
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CrawlScope {
+    /// Pages on the same origin whose path starts with the start URL's directory
+    /// (e.g. everything under `/docs/` when starting from `/docs/` or `/docs/index.html`)
+    #[default]
+    PathPrefix,
+    /// Any page on the same origin (scheme, host and port) as the start URL
+    SameOrigin,
+}
+
+/// Limits of a crawl
+#[derive(Debug, Clone)]
+pub struct CrawlOptions {
+    /// Maximum link distance from the start URL (0 fetches only the start URL)
+    pub max_depth: usize,
+    /// Maximum number of pages to fetch, including failed ones
+    pub max_pages: usize,
+    /// Which links may be followed
+    pub scope: CrawlScope,
+    /// Output format of the extracted page content
+    pub format: ContentFormat,
+}
+
+impl Default for CrawlOptions {
+    fn default() -> Self {
+        Self {
+            max_depth: 2,
+            max_pages: 20,
+            scope: CrawlScope::default(),
+            format: ContentFormat::default(),
+        }
+    }
+}
+
+/// A page read during a crawl
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CrawledPage {
+    /// Final URL of the page
+    pub url: String,
+    /// Link distance from the start URL
+    pub depth: usize,
+    /// The title of the page (if available)
+    pub title: Option<String>,
+    /// The main text content of the page
+    pub text: String,
+    /// Whether the page was cut because its body exceeded the size limit
+    #[serde(default)]
+    pub truncated: bool,
+}
+
+/// A page that could not be read during a crawl
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CrawlFailure {
+    pub url: String,
+    /// Why the page was not read (robots.txt, HTTP status, content type, ...)
+    pub reason: String,
+}
+
+/// Result of a crawl, in breadth-first order
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CrawlResult {
+    pub pages: Vec<CrawledPage>,
+    pub failures: Vec<CrawlFailure>,
+}
+
+/// Crawl breadth-first from `start_url`, following in-scope links up to the configured
+/// depth and page count. Links kept off by the domain policy are not followed; pages
+/// disallowed by robots.txt, failing to load or redirecting out of scope are recorded
+/// as failures and the crawl goes on.
+///
+/// # Errors
+/// Returns an error only if `start_url` is not an http(s) URL or the domain policy
+/// blocks it.
+pub(crate) async fn crawl<C: HttpClient>(
+    context: &FetchContext<C>,
+    start_url: &str,
+    options: &CrawlOptions,
+) -> Result<CrawlResult, FetchError> {
+    let mut start = Url::parse(start_url).map_err(anyhow::Error::from)?;
+    if !matches!(start.scheme(), "http" | "https") {
+        return Err(anyhow::anyhow!("Cannot crawl {}: not an http(s) URL", start_url).into());
+    }
+    context.domain_policy.check(start_url)?;
+    start.set_fragment(None);
+    let scope = Scope::new(&start, options.scope);
+
+    let mut result = CrawlResult::default();
+    let mut seen: HashSet<String> = HashSet::from([start.to_string()]);
+    let mut queue: VecDeque<(Url, usize)> = VecDeque::from([(start, 0)]);
+    let mut fetched = 0;
+
+    while let Some((url, depth)) = queue.pop_front() {
+        if fetched >= options.max_pages {
+            break;
+        }
+        fetched += 1;
+
+        debug!("Crawling {} (depth {})", url, depth);
+        let page = match fetch_url(context, url.as_str(), options.format).await {
+            Ok(page) => page,
+            Err(e) => {
+                debug!("Skipping {}: {}", url, e);
+                result.failures.push(CrawlFailure {
+                    url: url.to_string(),
+                    reason: e.to_string(),
+                });
+                continue;
+            }
+        };
+        // Don't read a page twice when another link redirected to it
+        seen.insert(page.url.clone());
+        if !Url::parse(&page.url).is_ok_and(|final_url| scope.contains(&final_url)) {
+            debug!("Skipping {}: redirected out of scope to {}", url, page.url);
+            result.failures.push(CrawlFailure {
+                url: url.to_string(),
+                reason: format!("Redirected out of the crawl scope to {}", page.url),
+            });
+            continue;
+        }
+
+        if depth < options.max_depth {
+            for link in &page.links {
+                let Ok(link_url) = Url::parse(&link.url) else {
+                    continue;
+                };
+                if scope.contains(&link_url)
+                    && !has_skipped_extension(&link_url)
+                    && context.domain_policy.allows(link_url.as_str())
+                    && seen.insert(link_url.to_string())
+                {
+                    queue.push_back((link_url, depth + 1));
+                }
+            }
+        }
+
+        result.pages.push(CrawledPage {
+            url: page.url,
+            depth,
+            title: page.title,
+            text: page.text,
+            truncated: page.truncated,
+        });
+    }
+
+    info!(
+        "Crawled {} pages from {} ({} failed)",
+        result.pages.len(),
+        start_url,
+        result.failures.len()
+    );
+    Ok(result)
+}
+
+/// The set of URLs a crawl may visit
+struct Scope {
+    /// Serialized origin, e.g. "https://example.com:8443"
+    origin: String,
+    path_prefix: Option<String>,
+}
+
+impl Scope {
+    fn new(start: &Url, scope: CrawlScope) -> Self {
+        let path_prefix = match scope {
+            CrawlScope::SameOrigin => None,
+            CrawlScope::PathPrefix => {
+                let path = start.path();
+                Some(path[..=path.rfind('/').unwrap_or(0)].to_string())
+            }
+        };
+        Self {
+            origin: start.origin().ascii_serialization(),
+            path_prefix,
+        }
+    }
+
+    fn contains(&self, url: &Url) -> bool {
+        url.origin().ascii_serialization() == self.origin
+            && self
+                .path_prefix
+                .as_ref()
+                .is_none_or(|prefix| url.path().starts_with(prefix.as_str()))
+    }
+}
+
+fn has_skipped_extension(url: &Url) -> bool {
+    url.path().rsplit_once('.').is_some_and(|(_, extension)| {
+        SKIPPED_EXTENSIONS.contains(&extension.to_lowercase().as_str())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::collectors::domain_policy::DomainPolicy;
+    use crate::collectors::politeness::PolitenessConfig;
+    use crate::collectors::url_policy::UrlPolicy;
+    use crate::collectors::web::{HttpConfig, ReqwestClient};
+    use std::time::{Duration, Instant};
+    use wiremock::matchers::{method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    /// Serve an HTML page at `page_path` on the stand-in site
+    async fn page(server: &MockServer, page_path: &str, title: &str, body: &str) {
+        let html = format!(
+            "<html><head><title>{}</title></head><body>{}</body></html>",
+            title, body
+        );
+        Mock::given(method("GET"))
+            .and(path(page_path))
+            .respond_with(ResponseTemplate::new(200).set_body_raw(html, "text/html"))
+            .mount(server)
+            .await;
+    }
+
+    /// A small documentation site:
+    /// /docs/ -> intro, guide, /blog/, external site; guide -> advanced; advanced -> /docs/
+    async fn docs_site() -> MockServer {
+        let server = MockServer::start().await;
+        page(
+            &server,
+            "/docs/",
+            "Docs",
+            r#"<p>Documentation home page for the project.</p>
+               <a href="intro">Intro</a> <a href="guide">Guide</a>
+               <a href="/blog/">Blog</a> <a href="https://other.example/">Elsewhere</a>
+               <a href="logo.png">Logo</a>"#,
+        )
+        .await;
+        page(
+            &server,
+            "/docs/intro",
+            "Intro",
+            "<p>An introduction to the project and its goals.</p>",
+        )
+        .await;
+        page(
+            &server,
+            "/docs/guide",
+            "Guide",
+            r#"<p>A guide for everyday use of the project.</p><a href="advanced">Advanced</a>"#,
+        )
+        .await;
+        page(
+            &server,
+            "/docs/advanced",
+            "Advanced",
+            r#"<p>Advanced topics for experienced users.</p><a href="/docs/">Home</a>"#,
+        )
+        .await;
+        page(
+            &server,
+            "/blog/",
+            "Blog",
+            "<p>News and announcements about the project.</p>",
+        )
+        .await;
+        server
+    }
+
+    fn context() -> FetchContext {
+        let politeness = PolitenessConfig {
+            min_interval: Duration::ZERO,
+            ..PolitenessConfig::default()
+        };
+        FetchContext::new(
+            ReqwestClient::new(&HttpConfig::default()).unwrap(),
+            politeness,
+        )
+        .with_url_policy(UrlPolicy::default().allow_host("127.0.0.1"))
+    }
+
+    fn titles(result: &CrawlResult) -> Vec<&str> {
+        result
+            .pages
+            .iter()
+            .map(|p| p.title.as_deref().unwrap_or_default())
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_crawl_is_breadth_first_within_path_prefix() {
+        let server = docs_site().await;
+
+        let result = crawl(
+            &context(),
+            &format!("{}/docs/", server.uri()),
+            &CrawlOptions::default(),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(titles(&result), vec!["Docs", "Intro", "Guide", "Advanced"]);
+        assert_eq!(
+            result.pages.iter().map(|p| p.depth).collect::<Vec<_>>(),
+            vec![0, 1, 1, 2]
+        );
+        assert_eq!(
+            result.pages[1].text,
+            "An introduction to the project and its goals."
+        );
+        assert!(result.failures.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_crawl_same_origin_leaves_path_prefix() {
+        let server = docs_site().await;
+        let options = CrawlOptions {
+            max_depth: 1,
+            scope: CrawlScope::SameOrigin,
+            ..CrawlOptions::default()
+        };
+
+        let result = crawl(&context(), &format!("{}/docs/", server.uri()), &options)
+            .await
+            .unwrap();
+
+        assert_eq!(titles(&result), vec!["Docs", "Intro", "Guide", "Blog"]);
+    }
+
+    #[tokio::test]
+    async fn test_crawl_stops_at_max_depth_and_max_pages() {
+        let server = docs_site().await;
+        let start = format!("{}/docs/", server.uri());
+
+        let shallow = crawl(
+            &context(),
+            &start,
+            &CrawlOptions {
+                max_depth: 0,
+                ..CrawlOptions::default()
+            },
+        )
+        .await
+        .unwrap();
+        let limited = crawl(
+            &context(),
+            &start,
+            &CrawlOptions {
+                max_pages: 2,
+                ..CrawlOptions::default()
+            },
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(titles(&shallow), vec!["Docs"]);
+        assert_eq!(titles(&limited), vec!["Docs", "Intro"]);
+    }
+
+    #[tokio::test]
+    async fn test_crawl_skips_pages_disallowed_by_robots_txt() {
+        let server = docs_site().await;
+        Mock::given(method("GET"))
+            .and(path("/robots.txt"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_raw("User-agent: *\nDisallow: /docs/guide", "text/plain"),
+            )
+            .mount(&server)
+            .await;
+
+        let result = crawl(
+            &context(),
+            &format!("{}/docs/", server.uri()),
+            &CrawlOptions::default(),
+        )
+        .await
+        .unwrap();
+
+        // The guide is never read, so the advanced page is never discovered
+        assert_eq!(titles(&result), vec!["Docs", "Intro"]);
+        assert_eq!(result.failures.len(), 1);
+        assert_eq!(
+            result.failures[0].url,
+            format!("{}/docs/guide", server.uri())
+        );
+        assert!(result.failures[0].reason.contains("robots.txt"));
+    }
+
+    #[tokio::test]
+    async fn test_crawl_honours_crawl_delay() {
+        let server = docs_site().await;
+        Mock::given(method("GET"))
+            .and(path("/robots.txt"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_raw("User-agent: *\nCrawl-delay: 0.2\nDisallow:", "text/plain"),
+            )
+            .mount(&server)
+            .await;
+        let options = CrawlOptions {
+            max_depth: 1,
+            ..CrawlOptions::default()
+        };
+        let started = Instant::now();
+
+        let result = crawl(&context(), &format!("{}/docs/", server.uri()), &options)
+            .await
+            .unwrap();
+
+        // Docs, then Intro and Guide each 0.2s after the previous page
+        assert_eq!(titles(&result), vec!["Docs", "Intro", "Guide"]);
+        assert!(started.elapsed() >= Duration::from_millis(400));
+    }
+
+    #[tokio::test]
+    async fn test_crawl_does_not_follow_links_denied_by_domain_policy() {
+        let server = docs_site().await;
+        let policy = DomainPolicy {
+            deny: vec!["127.0.0.1/docs/guide".parse().unwrap()],
+            ..DomainPolicy::default()
+        };
+
+        let result = crawl(
+            &context().with_domain_policy(policy),
+            &format!("{}/docs/", server.uri()),
+            &CrawlOptions::default(),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(titles(&result), vec!["Docs", "Intro"]);
+        assert!(result.failures.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_crawl_skips_pages_redirecting_out_of_scope() {
+        let server = MockServer::start().await;
+        page(
+            &server,
+            "/docs/",
+            "Docs",
+            r#"<p>Documentation home page for the project.</p><a href="moved">Moved</a>"#,
+        )
+        .await;
+        Mock::given(method("GET"))
+            .and(path("/docs/moved"))
+            .respond_with(ResponseTemplate::new(301).insert_header("location", "/blog/"))
+            .mount(&server)
+            .await;
+        page(
+            &server,
+            "/blog/",
+            "Blog",
+            r#"<p>News and announcements about the project.</p><a href="/docs/hidden">Hidden</a>"#,
+        )
+        .await;
+        page(
+            &server,
+            "/docs/hidden",
+            "Hidden",
+            "<p>Only linked from the blog.</p>",
+        )
+        .await;
+
+        let result = crawl(
+            &context(),
+            &format!("{}/docs/", server.uri()),
+            &CrawlOptions::default(),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(titles(&result), vec!["Docs"]);
+        assert_eq!(result.failures.len(), 1);
+        assert_eq!(
+            result.failures[0].url,
+            format!("{}/docs/moved", server.uri())
+        );
+        assert!(result.failures[0].reason.contains("out of the crawl scope"));
+    }
+
+    #[tokio::test]
+    async fn test_crawl_rejects_start_url_outside_allowlist() {
+        let server = docs_site().await;
+        let policy = DomainPolicy {
+            allow: vec!["*.example.org".parse().unwrap()],
+            ..DomainPolicy::default()
+        };
+
+        let result = crawl(
+            &context().with_domain_policy(policy),
+            &format!("{}/docs/", server.uri()),
+            &CrawlOptions::default(),
+        )
+        .await;
+
+        assert!(matches!(result, Err(FetchError::DomainBlocked(_))));
+        assert!(server.received_requests().await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_crawl_rejects_non_http_start_url() {
+        let result = crawl(&context(), "file:///etc/passwd", &CrawlOptions::default()).await;
+
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_path_prefix_scope_uses_start_directory() {
+        let start = Url::parse("https://example.com/docs/index.html").unwrap();
+        let scope = Scope::new(&start, CrawlScope::PathPrefix);
+
+        assert!(scope.contains(&Url::parse("https://example.com/docs/a/b").unwrap()));
+        assert!(!scope.contains(&Url::parse("https://example.com/blog/").unwrap()));
+        assert!(!scope.contains(&Url::parse("http://example.com/docs/a").unwrap()));
+        assert!(!scope.contains(&Url::parse("https://example.com:8443/docs/a").unwrap()));
+    }
+}
@@ -0,0 +1,213 @@
+use std::fmt;
+use std::str::FromStr;
+
+use reqwest::Url;
+use serde::Deserialize;
+
+/// Why a URL is kept off by the domain policy
+#[derive(Debug, thiserror::Error)]
+pub enum DomainBlocked {
+    #[error("{url} is blocked by the domain policy (denied by \"{rule}\")")]
+    Denied { url: String, rule: DomainRule },
+    #[error("{url} is blocked by the domain policy (not on the allowlist)")]
+    NotAllowed { url: String },
+}
+
+/// A domain, optionally with a path prefix:
+/// - `example.com` matches that host only
+/// - `*.example.com` matches example.com and all its subdomains
+/// - `example.com/docs/` (or `*.example.com/docs/`) also requires the path to start with `/docs/`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DomainRule {
+    host: String,
+    include_subdomains: bool,
+    path_prefix: Option<String>,
+}
+
+impl DomainRule {
+    fn matches(&self, url: &Url) -> bool {
+        let Some(host) = url.host_str() else {
+            return false;
+        };
+        let host = host.trim_end_matches('.').to_ascii_lowercase();
+        let host_matches = host == self.host
+            || (self.include_subdomains
+                && host
+                    .strip_suffix(&self.host)
+                    .is_some_and(|sub| sub.ends_with('.')));
+        host_matches
+            && self
+                .path_prefix
+                .as_ref()
+                .is_none_or(|prefix| url.path().starts_with(prefix.as_str()))
+    }
+}
+
+impl FromStr for DomainRule {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let rule = s.trim();
+        // Tolerate rules written as URLs
+        let rule = rule
+            .strip_prefix("https://")
+            .or_else(|| rule.strip_prefix("http://"))
+            .unwrap_or(rule);
+        let (host, path_prefix) = match rule.find('/') {
+            Some(slash) => (&rule[..slash], Some(rule[slash..].to_string())),
+            None => (rule, None),
+        };
+        let (host, include_subdomains) = match host.strip_prefix("*.") {
+            Some(domain) => (domain, true),
+            None => (host, false),
+        };
+        let host = host.trim_end_matches('.').to_ascii_lowercase();
+        if host.is_empty() || host.contains(['*', ':', ' ']) {
+            return Err(format!(
+                "invalid domain rule \"{}\" (expected e.g. \"example.com\", \"*.example.com\" or \"example.com/docs/\")",
+                s
+            ));
+        }
+        Ok(Self {
+            host,
+            include_subdomains,
+            path_prefix,
+        })
+    }
+}
+
+impl fmt::Display for DomainRule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.include_subdomains {
+            write!(f, "*.")?;
+        }
+        write!(
+            f,
+            "{}{}",
+            self.host,
+            self.path_prefix.as_deref().unwrap_or("")
+        )
+    }
+}
+
+impl<'de> Deserialize<'de> for DomainRule {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(serde::de::Error::custom)
+    }
+}
+
+/// Which sites the agent may use: a denylist, and optionally an allowlist.
+/// Denied rules win over allowed ones; an empty allowlist allows every site.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct DomainPolicy {
+    #[serde(default)]
+    pub allow: Vec<DomainRule>,
+    #[serde(default)]
+    pub deny: Vec<DomainRule>,
+}
+
+impl DomainPolicy {
+    /// Check whether `url` may be fetched
+    pub fn check(&self, url: &str) -> Result<(), DomainBlocked> {
+        // Unparsable URLs are left to the request to reject
+        let Ok(parsed) = Url::parse(url) else {
+            return Ok(());
+        };
+        if let Some(rule) = self.deny.iter().find(|rule| rule.matches(&parsed)) {
+            return Err(DomainBlocked::Denied {
+                url: url.to_string(),
+                rule: rule.clone(),
+            });
+        }
+        if !self.allow.is_empty() && !self.allow.iter().any(|rule| rule.matches(&parsed)) {
+            return Err(DomainBlocked::NotAllowed {
+                url: url.to_string(),
+            });
+        }
+        Ok(())
+    }
+
+    /// Whether `url` may be fetched
+    pub fn allows(&self, url: &str) -> bool {
+        self.check(url).is_ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rules(rules: &[&str]) -> Vec<DomainRule> {
+        rules.iter().map(|rule| rule.parse().unwrap()).collect()
+    }
+
+    #[test]
+    fn test_exact_host_rule() {
+        let policy = DomainPolicy {
+            deny: rules(&["example.com"]),
+            ..DomainPolicy::default()
+        };
+
+        assert!(!policy.allows("https://example.com/page"));
+        assert!(!policy.allows("http://EXAMPLE.com:8080/"));
+        assert!(policy.allows("https://www.example.com/"));
+        assert!(policy.allows("https://notexample.com/"));
+    }
+
+    #[test]
+    fn test_wildcard_rule_matches_domain_and_subdomains() {
+        let policy = DomainPolicy {
+            deny: rules(&["*.medium.com"]),
+            ..DomainPolicy::default()
+        };
+
+        assert!(!policy.allows("https://medium.com/"));
+        assert!(!policy.allows("https://blog.medium.com/post"));
+        assert!(!policy.allows("https://a.b.medium.com/"));
+        assert!(policy.allows("https://notmedium.com/"));
+    }
+
+    #[test]
+    fn test_path_prefix_rule() {
+        let policy = DomainPolicy {
+            deny: rules(&["example.com/private/"]),
+            ..DomainPolicy::default()
+        };
+
+        assert!(!policy.allows("https://example.com/private/report"));
+        assert!(policy.allows("https://example.com/public/report"));
+        assert!(policy.allows("https://example.com/private"));
+    }
+
+    #[test]
+    fn test_allowlist_restricts_to_listed_sites_and_deny_wins() {
+        let policy = DomainPolicy {
+            allow: rules(&["*.rust-lang.org", "docs.rs"]),
+            deny: rules(&["blog.rust-lang.org"]),
+        };
+
+        assert!(policy.allows("https://doc.rust-lang.org/std/"));
+        assert!(policy.allows("https://docs.rs/serde"));
+        assert!(matches!(
+            policy.check("https://blog.rust-lang.org/"),
+            Err(DomainBlocked::Denied { rule, .. }) if rule.to_string() == "blog.rust-lang.org"
+        ));
+        assert!(matches!(
+            policy.check("https://example.com/"),
+            Err(DomainBlocked::NotAllowed { .. })
+        ));
+    }
+
+    #[test]
+    fn test_parse_rule() {
+        let rule: DomainRule = "https://*.Example.com/docs/".parse().unwrap();
+        assert_eq!(rule.to_string(), "*.example.com/docs/");
+
+        assert!("".parse::<DomainRule>().is_err());
+        assert!("*".parse::<DomainRule>().is_err());
+        assert!("exa*mple.com".parse::<DomainRule>().is_err());
+    }
+}
@@ -0,0 +1,239 @@
+use encoding_rs::{Encoding, UTF_16BE, UTF_16LE, UTF_8, WINDOWS_1252, X_USER_DEFINED};
+
+/// Number of bytes scanned for a `<meta>` charset declaration (as in the WHATWG spec)
+const META_PRESCAN_BYTES: usize = 1024;
+
+/// Decode an HTML document, detecting its character encoding the way browsers do:
+/// a byte order mark wins, then the Content-Type `charset` parameter,
+/// then a `<meta charset>` / `<meta http-equiv="Content-Type">` declaration near the top
+/// of the document. Undeclared documents are read as UTF-8 when valid, else windows-1252.
+pub(crate) fn decode_html(body: &[u8], header_charset: Option<&str>) -> String {
+    let encoding = header_charset
+        .and_then(encoding_for_label)
+        .or_else(|| prescan_meta_charset(body))
+        .unwrap_or_else(|| fallback_encoding(body));
+
+    // `decode` lets a byte order mark override the detected encoding
+    let (text, _, _) = encoding.decode(body);
+    text.into_owned()
+}
+
+/// Decode a non-HTML text body using its BOM or Content-Type `charset` parameter
+pub(crate) fn decode_text(body: &[u8], header_charset: Option<&str>) -> String {
+    let encoding = header_charset
+        .and_then(encoding_for_label)
+        .unwrap_or_else(|| fallback_encoding(body));
+
+    let (text, _, _) = encoding.decode(body);
+    text.into_owned()
+}
+
+fn encoding_for_label(label: &str) -> Option<&'static Encoding> {
+    Encoding::for_label(label.trim().as_bytes())
+}
+
+fn fallback_encoding(body: &[u8]) -> &'static Encoding {
+    if std::str::from_utf8(body).is_ok() {
+        UTF_8
+    } else {
+        WINDOWS_1252
+    }
+}
+
+/// Look for a charset declared by a `<meta>` tag in the first bytes of the document
+fn prescan_meta_charset(body: &[u8]) -> Option<&'static Encoding> {
+    let head = &body[..body.len().min(META_PRESCAN_BYTES)];
+    // Charset labels are ASCII, so a lossy decode is enough to find them
+    let head = String::from_utf8_lossy(head).to_ascii_lowercase();
+
+    let mut rest = head.as_str();
+    while let Some(start) = rest.find("<meta") {
+        let tag = &rest[start + "<meta".len()..];
+        let end = tag.find('>').unwrap_or(tag.len());
+        if let Some(encoding) = meta_tag_charset(&tag[..end]) {
+            // A UTF-16 declaration can't be right for a document readable as ASCII,
+            // so the spec maps it to UTF-8 (and x-user-defined to windows-1252)
+            return Some(match encoding {
+                e if e == UTF_16LE || e == UTF_16BE => UTF_8,
+                e if e == X_USER_DEFINED => WINDOWS_1252,
+                e => e,
+            });
+        }
+        rest = &tag[end..];
+    }
+    None
+}
+
+/// Extract the charset from the attributes of a single `<meta>` tag
+fn meta_tag_charset(attributes: &str) -> Option<&'static Encoding> {
+    let attributes = parse_attributes(attributes);
+    let get = |name: &str| {
+        attributes
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, v)| v.as_str())
+    };
+
+    if let Some(charset) = get("charset") {
+        return encoding_for_label(charset);
+    }
+    if get("http-equiv") == Some("content-type") {
+        return get("content")
+            .and_then(charset_param)
+            .and_then(encoding_for_label);
+    }
+    None
+}
+
+/// Extract the `charset` parameter from a Content-Type value such as `text/html; charset=utf-8`
+pub(crate) fn charset_param(content_type: &str) -> Option<&str> {
+    content_type.split(';').skip(1).find_map(|param| {
+        let (name, value) = param.split_once('=')?;
+        if !name.trim().eq_ignore_ascii_case("charset") {
+            return None;
+        }
+        let value = value.trim().trim_matches(|c| c == '"' || c == '\'');
+        (!value.is_empty()).then_some(value)
+    })
+}
+
+/// Parse `name=value`, `name="value"` and bare `name` attributes
+fn parse_attributes(input: &str) -> Vec<(String, String)> {
+    let mut attributes = Vec::new();
+    let mut chars = input.char_indices().peekable();
+
+    while let Some(&(start, c)) = chars.peek() {
+        if c.is_whitespace() || c == '/' {
+            chars.next();
+            continue;
+        }
+
+        // Attribute name
+        let mut end = start;
+        while let Some(&(i, c)) = chars.peek() {
+            if c.is_whitespace() || c == '=' || c == '/' {
+                break;
+            }
+            end = i + c.len_utf8();
+            chars.next();
+        }
+        let name = input[start..end].to_string();
+
+        while chars.next_if(|(_, c)| c.is_whitespace()).is_some() {}
+        if chars.next_if(|(_, c)| *c == '=').is_none() {
+            attributes.push((name, String::new()));
+            continue;
+        }
+        while chars.next_if(|(_, c)| c.is_whitespace()).is_some() {}
+
+        // Attribute value, quoted or not
+        let mut value = String::new();
+        match chars.peek() {
+            Some(&(_, quote)) if quote == '"' || quote == '\'' => {
+                chars.next();
+                for (_, c) in chars.by_ref() {
+                    if c == quote {
+                        break;
+                    }
+                    value.push(c);
+                }
+            }
+            _ => {
+                while let Some((_, c)) = chars.next_if(|(_, c)| !c.is_whitespace()) {
+                    value.push(c);
+                }
+            }
+        }
+        attributes.push((name, value));
+    }
+    attributes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SHIFT_JIS_PAGE: &[u8] = include_bytes!("../../tests/fixtures/html/shift_jis.html");
+    const EUC_JP_PAGE: &[u8] = include_bytes!("../../tests/fixtures/html/euc_jp.html");
+
+    #[test]
+    fn test_decode_shift_jis_from_meta_charset() {
+        let html = decode_html(SHIFT_JIS_PAGE, None);
+
+        assert!(html.contains("<title>日本語のページ</title>"));
+        assert!(html.contains("これはシフトJISで書かれた文章です。"));
+    }
+
+    #[test]
+    fn test_decode_euc_jp_from_meta_http_equiv() {
+        let html = decode_html(EUC_JP_PAGE, None);
+
+        assert!(html.contains("<title>天気予報</title>"));
+        assert!(html.contains("東京は明日、晴れのち曇りでしょう。"));
+    }
+
+    #[test]
+    fn test_header_charset_takes_precedence_over_meta() {
+        // The header is right and the meta tag is wrong
+        let body = b"<meta charset=\"utf-8\"><p>caf\xe9</p>";
+
+        let html = decode_html(body, Some("ISO-8859-1"));
+
+        assert_eq!(html, "<meta charset=\"utf-8\"><p>café</p>");
+    }
+
+    #[test]
+    fn test_bom_takes_precedence_over_header() {
+        let body = b"\xef\xbb\xbf<p>caf\xc3\xa9</p>";
+
+        let html = decode_html(body, Some("windows-1252"));
+
+        assert_eq!(html, "<p>café</p>");
+    }
+
+    #[test]
+    fn test_undeclared_encoding_falls_back_to_utf8_or_windows_1252() {
+        assert_eq!(decode_html("<p>café</p>".as_bytes(), None), "<p>café</p>");
+        assert_eq!(decode_html(b"<p>caf\xe9</p>", None), "<p>café</p>");
+    }
+
+    #[test]
+    fn test_meta_declaring_utf16_is_read_as_utf8() {
+        let body = "<meta charset=\"utf-16\"><p>café</p>".as_bytes();
+
+        assert_eq!(
+            decode_html(body, None),
+            "<meta charset=\"utf-16\"><p>café</p>"
+        );
+    }
+
+    #[test]
+    fn test_decode_text_uses_header_charset() {
+        assert_eq!(decode_text(b"caf\xe9", Some("latin1")), "café");
+    }
+
+    #[test]
+    fn test_charset_param() {
+        assert_eq!(
+            charset_param("text/html; charset=Shift_JIS"),
+            Some("Shift_JIS")
+        );
+        assert_eq!(charset_param("text/html;charset=\"utf-8\""), Some("utf-8"));
+        assert_eq!(charset_param("text/html"), None);
+    }
+
+    #[test]
+    fn test_parse_attributes() {
+        let attributes =
+            parse_attributes(r#" http-equiv='Content-Type' content="text/html; charset=x" async"#);
+
+        assert_eq!(
+            attributes,
+            vec![
+                ("http-equiv".to_string(), "Content-Type".to_string()),
+                ("content".to_string(), "text/html; charset=x".to_string()),
+                ("async".to_string(), String::new()),
+            ]
+        );
+    }
+}
@@ -0,0 +1,795 @@
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use anyhow::Result;
+use log::{debug, warn};
+use reqwest::header::{
+    HeaderMap, HeaderName, HeaderValue, CACHE_CONTROL, CONTENT_LENGTH, ETAG, IF_MODIFIED_SINCE,
+    IF_NONE_MATCH, LAST_MODIFIED, VARY,
+};
+use reqwest::StatusCode;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use super::web::HttpResponse;
+
+/// Longest time a stored response is used without revalidation, whatever its
+/// `max-age` (this is also the RFC 9309 limit for robots.txt)
+const MAX_FRESHNESS: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// Default total size of the stored entries, beyond which the least recently used go
+const DEFAULT_MAX_BYTES: u64 = 256 * 1024 * 1024;
+
+/// How the on-disk HTTP cache is used
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum CacheMode {
+    /// Serve fresh responses from the cache and revalidate stale ones
+    #[default]
+    Normal,
+    /// Ignore stored responses, but store the new ones
+    Refresh,
+    /// Neither read nor write the cache
+    Disabled,
+}
+
+/// On-disk cache of successful GET responses (pages and robots.txt), shared across sessions.
+///
+/// Responses are fresh for their `Cache-Control: max-age` (at most `MAX_FRESHNESS`);
+/// stale responses with an `ETag` or `Last-Modified` are revalidated with a conditional
+/// request. Responses marked `no-store`, varying on request headers, or that could
+/// never be reused, are not stored. Entries are keyed by URL and by whether redirects
+/// were followed; once they take up more than the size cap, the least recently used
+/// ones are removed.
+pub struct HttpCache {
+    dir: PathBuf,
+    mode: CacheMode,
+    max_bytes: u64,
+    /// Total size of the entries when last counted, plus this session's changes;
+    /// `None` until the first write counts them
+    total_bytes: Mutex<Option<u64>>,
+}
+
+/// Result of looking up a URL in the cache
+pub(crate) enum CacheLookup {
+    /// Stored response that can be used as-is
+    Fresh(HttpResponse),
+    /// Stored response that must be revalidated before use
+    Stale(HttpResponse),
+    Miss,
+}
+
+/// Metadata stored before a response body, on the first line of its entry file
+#[derive(Serialize, Deserialize)]
+struct EntryMeta {
+    /// Requested URL
+    url: String,
+    /// Whether redirects were followed to get the response
+    follow_redirects: bool,
+    /// Final URL after redirects
+    final_url: String,
+    status: u16,
+    headers: Vec<(String, String)>,
+    /// Seconds since the Unix epoch when the response was stored or last revalidated
+    stored_at: u64,
+}
+
+impl HttpCache {
+    /// Create a cache storing its entries in `dir` (created on first write)
+    pub fn new(dir: impl Into<PathBuf>, mode: CacheMode) -> Self {
+        Self {
+            dir: dir.into(),
+            mode,
+            max_bytes: DEFAULT_MAX_BYTES,
+            total_bytes: Mutex::new(None),
+        }
+    }
+
+    /// Keep the stored entries under `max_bytes` in total
+    pub fn with_max_bytes(mut self, max_bytes: u64) -> Self {
+        self.max_bytes = max_bytes;
+        self
+    }
+
+    /// `$XDG_CACHE_HOME/cipherant/http` (or the platform's cache directory)
+    pub fn default_dir() -> Option<PathBuf> {
+        dirs::cache_dir().map(|dir| dir.join("cipherant").join("http"))
+    }
+
+    /// Look up the stored response for `url`, fetched with or without following redirects
+    pub(crate) async fn lookup(&self, url: &str, follow_redirects: bool) -> CacheLookup {
+        if self.mode != CacheMode::Normal {
+            return CacheLookup::Miss;
+        }
+        let (meta, body) = match self.read_entry(url, follow_redirects).await {
+            Ok(Some(entry)) => entry,
+            Ok(None) => return CacheLookup::Miss,
+            Err(e) => {
+                debug!("Ignoring unreadable cache entry for {}: {}", url, e);
+                return CacheLookup::Miss;
+            }
+        };
+
+        touch(self.entry_path(url, follow_redirects)).await;
+
+        let age = unix_now().saturating_sub(meta.stored_at);
+        let response = HttpResponse {
+            status: StatusCode::from_u16(meta.status).unwrap_or(StatusCode::OK),
+            url: meta.final_url,
+            headers: to_header_map(&meta.headers),
+            body,
+            truncated: false,
+        };
+        if Duration::from_secs(age) < freshness_lifetime(&response.headers) {
+            CacheLookup::Fresh(response)
+        } else {
+            CacheLookup::Stale(response)
+        }
+    }
+
+    /// Store a response for `url`, if it is storable. Failures are only logged.
+    pub(crate) async fn store(&self, url: &str, follow_redirects: bool, response: &HttpResponse) {
+        if self.mode == CacheMode::Disabled {
+            return;
+        }
+        let result = if is_storable(response) {
+            let replaced = self.entry_size(url, follow_redirects).await;
+            match self
+                .write_entry(url, follow_redirects, response, SystemTime::now())
+                .await
+            {
+                Ok(written) if self.update_total(written, replaced) => self.sweep().await,
+                Ok(_) => Ok(()),
+                Err(e) => Err(e),
+            }
+        } else {
+            self.remove_entry(url, follow_redirects).await
+        };
+        if let Err(e) = result {
+            warn!("Failed to update the HTTP cache for {}: {}", url, e);
+        }
+    }
+
+    /// Refresh a stale response after the server answered 304 Not Modified:
+    /// its headers are updated from the 304 and it is fresh again
+    pub(crate) async fn revalidated(
+        &self,
+        url: &str,
+        follow_redirects: bool,
+        mut stored: HttpResponse,
+        not_modified: &HttpResponse,
+    ) -> HttpResponse {
+        for (name, value) in &not_modified.headers {
+            if name != CONTENT_LENGTH {
+                stored.headers.insert(name, value.clone());
+            }
+        }
+        stored.status = StatusCode::OK;
+        self.store(url, follow_redirects, &stored).await;
+        stored
+    }
+
+    async fn read_entry(
+        &self,
+        url: &str,
+        follow_redirects: bool,
+    ) -> Result<Option<(EntryMeta, Vec<u8>)>> {
+        let mut entry = match tokio::fs::read(self.entry_path(url, follow_redirects)).await {
+            Ok(entry) => entry,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e.into()),
+        };
+        let split = entry
+            .iter()
+            .position(|&b| b == b'\n')
+            .ok_or_else(|| anyhow::anyhow!("missing metadata line"))?;
+        let meta: EntryMeta = serde_json::from_slice(&entry[..split])?;
+        // Guard against (unlikely) hash collisions
+        if meta.url != url || meta.follow_redirects != follow_redirects {
+            return Ok(None);
+        }
+        let body = entry.split_off(split + 1);
+        Ok(Some((meta, body)))
+    }
+
+    async fn write_entry(
+        &self,
+        url: &str,
+        follow_redirects: bool,
+        response: &HttpResponse,
+        stored_at: SystemTime,
+    ) -> Result<u64> {
+        let meta = EntryMeta {
+            url: url.to_string(),
+            follow_redirects,
+            final_url: response.url.clone(),
+            status: response.status.as_u16(),
+            headers: response
+                .headers
+                .iter()
+                .filter_map(|(name, value)| {
+                    Some((name.to_string(), value.to_str().ok()?.to_string()))
+                })
+                .collect(),
+            stored_at: stored_at
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_secs(),
+        };
+
+        tokio::fs::create_dir_all(&self.dir).await?;
+        // Metadata and body live in one file replaced atomically, so that a reader
+        // never sees the metadata of one write with the body of another.
+        // Compact JSON has no newline, which ends the metadata line.
+        let mut entry = serde_json::to_vec(&meta)?;
+        entry.push(b'\n');
+        entry.extend_from_slice(&response.body);
+        write_atomically(&self.entry_path(url, follow_redirects), &entry).await?;
+        Ok(entry.len() as u64)
+    }
+
+    async fn remove_entry(&self, url: &str, follow_redirects: bool) -> Result<()> {
+        let removed = self.entry_size(url, follow_redirects).await;
+        remove_file(&self.entry_path(url, follow_redirects)).await?;
+        self.update_total(0, removed);
+        Ok(())
+    }
+
+    /// Size of the stored entry, 0 if there is none
+    async fn entry_size(&self, url: &str, follow_redirects: bool) -> u64 {
+        tokio::fs::metadata(self.entry_path(url, follow_redirects))
+            .await
+            .map_or(0, |metadata| metadata.len())
+    }
+
+    /// Account for `added` bytes written and `removed` bytes replaced or deleted.
+    /// Returns whether the entries must be swept: they are over the size cap, or
+    /// haven't been counted yet.
+    fn update_total(&self, added: u64, removed: u64) -> bool {
+        let mut total_bytes = self.total_bytes.lock().unwrap();
+        match total_bytes.as_mut() {
+            Some(total) => {
+                *total = (*total + added).saturating_sub(removed);
+                *total > self.max_bytes
+            }
+            None => true,
+        }
+    }
+
+    /// Count the entries (other sessions may have added some), and remove the least
+    /// recently used ones until the rest fit in `max_bytes`
+    async fn sweep(&self) -> Result<()> {
+        let mut entries = Vec::new();
+        let mut total = 0;
+        let mut dir = tokio::fs::read_dir(&self.dir).await?;
+        while let Some(entry) = dir.next_entry().await? {
+            let path = entry.path();
+            if path
+                .extension()
+                .is_none_or(|extension| extension != "entry")
+            {
+                continue;
+            }
+            // Another session may have removed it meanwhile
+            let Ok(metadata) = entry.metadata().await else {
+                continue;
+            };
+            total += metadata.len();
+            let used_at = metadata.modified().unwrap_or(UNIX_EPOCH);
+            entries.push((used_at, metadata.len(), path));
+        }
+
+        entries.sort_unstable_by_key(|(used_at, ..)| *used_at);
+        let mut result = Ok(());
+        for (_, size, path) in entries {
+            if total <= self.max_bytes {
+                break;
+            }
+            debug!("Evicting {} from the HTTP cache", path.display());
+            result = remove_file(&path).await;
+            if result.is_err() {
+                break;
+            }
+            total -= size;
+        }
+        *self.total_bytes.lock().unwrap() = Some(total);
+        result
+    }
+
+    fn entry_path(&self, url: &str, follow_redirects: bool) -> PathBuf {
+        let mode = if follow_redirects { "follow" } else { "manual" };
+        let key = format!("{:x}", Sha256::digest(format!("{} {}", mode, url)));
+        self.dir.join(format!("{}.entry", key))
+    }
+}
+
+/// Conditional request headers to revalidate a stored response
+pub(crate) fn validators(stored: &HttpResponse) -> HeaderMap {
+    let mut headers = HeaderMap::new();
+    if let Some(etag) = stored.headers.get(ETAG) {
+        headers.insert(IF_NONE_MATCH, etag.clone());
+    }
+    if let Some(last_modified) = stored.headers.get(LAST_MODIFIED) {
+        headers.insert(IF_MODIFIED_SINCE, last_modified.clone());
+    }
+    headers
+}
+
+/// A response is worth storing if it succeeded, isn't `no-store`, doesn't depend on
+/// request headers, and can either be reused as-is for a while or revalidated
+fn is_storable(response: &HttpResponse) -> bool {
+    response.status == StatusCode::OK
+        && !response.truncated
+        && !has_directive(&response.headers, "no-store")
+        && !varies(&response.headers)
+        && (!freshness_lifetime(&response.headers).is_zero() || !validators(response).is_empty())
+}
+
+/// Whether the response varies on request headers other than `Accept-Encoding`,
+/// which the cache doesn't key on (the body is stored decompressed)
+fn varies(headers: &HeaderMap) -> bool {
+    headers
+        .get_all(VARY)
+        .into_iter()
+        .flat_map(|value| value.to_str().unwrap_or("*").split(','))
+        .map(str::trim)
+        .any(|name| !name.is_empty() && !name.eq_ignore_ascii_case("accept-encoding"))
+}
+
+/// How long a response may be used without revalidation, from `Cache-Control`
+fn freshness_lifetime(headers: &HeaderMap) -> Duration {
+    if has_directive(headers, "no-cache") {
+        return Duration::ZERO;
+    }
+    cache_directives(headers)
+        .find_map(|directive| {
+            let (name, value) = directive.split_once('=')?;
+            if !name.trim().eq_ignore_ascii_case("max-age") {
+                return None;
+            }
+            value.trim().trim_matches('"').parse::<u64>().ok()
+        })
+        .map(|seconds| Duration::from_secs(seconds).min(MAX_FRESHNESS))
+        .unwrap_or_default()
+}
+
+fn has_directive(headers: &HeaderMap, name: &str) -> bool {
+    cache_directives(headers).any(|directive| directive.eq_ignore_ascii_case(name))
+}
+
+fn cache_directives(headers: &HeaderMap) -> impl Iterator<Item = &str> {
+    headers
+        .get_all(CACHE_CONTROL)
+        .into_iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .map(str::trim)
+}
+
+fn to_header_map(headers: &[(String, String)]) -> HeaderMap {
+    let mut map = HeaderMap::new();
+    for (name, value) in headers {
+        if let (Ok(name), Ok(value)) = (
+            HeaderName::from_bytes(name.as_bytes()),
+            HeaderValue::from_str(value),
+        ) {
+            map.append(name, value);
+        }
+    }
+    map
+}
+
+/// Replace `path` with `contents` through a temporary file of its own in the same
+/// directory, so that concurrent writers (in this process or another session)
+/// never rename each other's half-written data into place
+async fn write_atomically(path: &Path, contents: &[u8]) -> Result<()> {
+    let mut tmp_name = path.file_name().unwrap_or_default().to_os_string();
+    tmp_name.push(format!(".{:016x}.tmp", fastrand::u64(..)));
+    let tmp_path = path.with_file_name(tmp_name);
+    let result = match tokio::fs::write(&tmp_path, contents).await {
+        Ok(()) => tokio::fs::rename(&tmp_path, path).await,
+        Err(e) => Err(e),
+    };
+    if result.is_err() {
+        let _ = tokio::fs::remove_file(&tmp_path).await;
+    }
+    Ok(result?)
+}
+
+async fn remove_file(path: &Path) -> Result<()> {
+    match tokio::fs::remove_file(path).await {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e.into()),
+        _ => Ok(()),
+    }
+}
+
+/// Mark an entry as just used, for the LRU sweep. Failures are ignored.
+async fn touch(path: PathBuf) {
+    let _ = tokio::task::spawn_blocking(move || {
+        std::fs::File::options()
+            .append(true)
+            .open(path)?
+            .set_modified(SystemTime::now())
+    })
+    .await;
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const URL: &str = "https://example.com/page";
+
+    fn response(headers: &[(&'static str, &str)]) -> HttpResponse {
+        let mut response = HttpResponse::new(URL, 200, "text/html", b"<p>cached</p>");
+        for (name, value) in headers {
+            response
+                .headers
+                .insert(HeaderName::from_static(name), value.parse().unwrap());
+        }
+        response
+    }
+
+    #[tokio::test]
+    async fn test_response_with_max_age_is_fresh() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = HttpCache::new(dir.path(), CacheMode::Normal);
+
+        cache
+            .store(
+                URL,
+                true,
+                &response(&[("cache-control", "public, max-age=600")]),
+            )
+            .await;
+
+        let CacheLookup::Fresh(stored) = cache.lookup(URL, true).await else {
+            panic!("expected a fresh response");
+        };
+        assert_eq!(stored.status, StatusCode::OK);
+        assert_eq!(stored.url, URL);
+        assert_eq!(stored.body, b"<p>cached</p>");
+        assert_eq!(stored.media_type(), Some("text/html".to_string()));
+    }
+
+    #[tokio::test]
+    async fn test_expired_response_is_stale() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = HttpCache::new(dir.path(), CacheMode::Normal);
+        let an_hour_ago = SystemTime::now() - Duration::from_secs(3600);
+
+        cache
+            .write_entry(
+                URL,
+                true,
+                &response(&[("cache-control", "max-age=600")]),
+                an_hour_ago,
+            )
+            .await
+            .unwrap();
+
+        assert!(matches!(
+            cache.lookup(URL, true).await,
+            CacheLookup::Stale(_)
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_max_age_is_capped_at_a_day() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = HttpCache::new(dir.path(), CacheMode::Normal);
+        let two_days_ago = SystemTime::now() - 2 * MAX_FRESHNESS;
+
+        cache
+            .write_entry(
+                URL,
+                true,
+                &response(&[("cache-control", "max-age=31536000")]),
+                two_days_ago,
+            )
+            .await
+            .unwrap();
+
+        assert!(matches!(
+            cache.lookup(URL, true).await,
+            CacheLookup::Stale(_)
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_response_with_validator_only_is_stored_stale() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = HttpCache::new(dir.path(), CacheMode::Normal);
+
+        cache
+            .store(URL, true, &response(&[("etag", "\"v1\"")]))
+            .await;
+
+        let CacheLookup::Stale(stored) = cache.lookup(URL, true).await else {
+            panic!("expected a stale response");
+        };
+        assert_eq!(validators(&stored)[IF_NONE_MATCH], "\"v1\"");
+    }
+
+    #[tokio::test]
+    async fn test_unreusable_responses_are_not_stored() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = HttpCache::new(dir.path(), CacheMode::Normal);
+        let not_found = HttpResponse::new("https://example.com/missing", 404, "text/html", b"");
+        let cut = HttpResponse {
+            truncated: true,
+            ..response(&[("cache-control", "max-age=600")])
+        };
+
+        cache
+            .store(
+                URL,
+                true,
+                &response(&[("cache-control", "no-store, max-age=600")]),
+            )
+            .await;
+        cache
+            .store("https://example.com/plain", true, &response(&[]))
+            .await;
+        cache
+            .store("https://example.com/missing", true, &not_found)
+            .await;
+        cache.store("https://example.com/cut", true, &cut).await;
+
+        assert!(matches!(cache.lookup(URL, true).await, CacheLookup::Miss));
+        assert!(matches!(
+            cache.lookup("https://example.com/plain", true).await,
+            CacheLookup::Miss
+        ));
+        assert!(matches!(
+            cache.lookup("https://example.com/missing", true).await,
+            CacheLookup::Miss
+        ));
+        assert!(matches!(
+            cache.lookup("https://example.com/cut", true).await,
+            CacheLookup::Miss
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_responses_varying_on_request_headers_are_not_stored() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = HttpCache::new(dir.path(), CacheMode::Normal);
+        let encoding = "https://example.com/encoding";
+
+        cache
+            .store(
+                URL,
+                true,
+                &response(&[
+                    ("cache-control", "max-age=600"),
+                    ("vary", "Accept-Language"),
+                ]),
+            )
+            .await;
+        cache
+            .store(
+                encoding,
+                true,
+                &response(&[
+                    ("cache-control", "max-age=600"),
+                    ("vary", "accept-encoding"),
+                ]),
+            )
+            .await;
+
+        assert!(matches!(cache.lookup(URL, true).await, CacheLookup::Miss));
+        assert!(matches!(
+            cache.lookup(encoding, true).await,
+            CacheLookup::Fresh(_)
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_entries_are_kept_apart_by_redirect_mode() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = HttpCache::new(dir.path(), CacheMode::Normal);
+        let mut redirect = HttpResponse::new(URL, 301, "text/html", b"");
+        redirect
+            .headers
+            .insert(CACHE_CONTROL, "max-age=600".parse().unwrap());
+
+        cache
+            .store(URL, true, &response(&[("cache-control", "max-age=600")]))
+            .await;
+        cache.store(URL, false, &redirect).await;
+
+        assert!(matches!(
+            cache.lookup(URL, true).await,
+            CacheLookup::Fresh(_)
+        ));
+        assert!(matches!(cache.lookup(URL, false).await, CacheLookup::Miss));
+    }
+
+    #[tokio::test]
+    async fn test_least_recently_used_entries_are_evicted_over_max_bytes() {
+        let dir = tempfile::tempdir().unwrap();
+        let stored = response(&[("cache-control", "max-age=600")]);
+        let entry_size = {
+            let probe = HttpCache::new(dir.path().join("probe"), CacheMode::Normal);
+            probe.store(URL, true, &stored).await;
+            std::fs::metadata(probe.entry_path(URL, true))
+                .unwrap()
+                .len()
+        };
+        let cache = HttpCache::new(dir.path().join("http"), CacheMode::Normal)
+            .with_max_bytes(2 * entry_size + entry_size / 2);
+        let (a, b, c) = (
+            "https://example.com/a",
+            "https://example.com/b",
+            "https://example.com/c",
+        );
+        let hour_ago = SystemTime::now() - Duration::from_secs(3600);
+
+        cache.store(a, true, &stored).await;
+        cache.store(b, true, &stored).await;
+        for url in [a, b] {
+            std::fs::File::options()
+                .append(true)
+                .open(cache.entry_path(url, true))
+                .unwrap()
+                .set_modified(hour_ago)
+                .unwrap();
+        }
+        // Using `a` makes `b` the least recently used entry
+        assert!(matches!(cache.lookup(a, true).await, CacheLookup::Fresh(_)));
+        cache.store(c, true, &stored).await;
+
+        assert!(matches!(cache.lookup(a, true).await, CacheLookup::Fresh(_)));
+        assert!(matches!(cache.lookup(b, true).await, CacheLookup::Miss));
+        assert!(matches!(cache.lookup(c, true).await, CacheLookup::Fresh(_)));
+    }
+
+    #[tokio::test]
+    async fn test_total_size_is_tracked_across_writes() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = HttpCache::new(dir.path(), CacheMode::Normal);
+        let stored = response(&[("cache-control", "max-age=600")]);
+        let on_disk = || -> u64 {
+            std::fs::read_dir(dir.path())
+                .unwrap()
+                .map(|entry| entry.unwrap().metadata().unwrap().len())
+                .sum()
+        };
+
+        cache.store(URL, true, &stored).await;
+        cache.store(URL, true, &stored).await;
+        cache
+            .store("https://example.com/other", true, &stored)
+            .await;
+        assert_eq!(*cache.total_bytes.lock().unwrap(), Some(on_disk()));
+
+        cache
+            .store(URL, true, &response(&[("cache-control", "no-store")]))
+            .await;
+        assert_eq!(*cache.total_bytes.lock().unwrap(), Some(on_disk()));
+    }
+
+    #[tokio::test]
+    async fn test_refresh_mode_skips_lookups_but_stores() {
+        let dir = tempfile::tempdir().unwrap();
+        let refresh = HttpCache::new(dir.path(), CacheMode::Refresh);
+        let normal = HttpCache::new(dir.path(), CacheMode::Normal);
+
+        refresh
+            .store(URL, true, &response(&[("cache-control", "max-age=600")]))
+            .await;
+
+        assert!(matches!(refresh.lookup(URL, true).await, CacheLookup::Miss));
+        assert!(matches!(
+            normal.lookup(URL, true).await,
+            CacheLookup::Fresh(_)
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_disabled_mode_stores_nothing() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = HttpCache::new(dir.path().join("http"), CacheMode::Disabled);
+
+        cache
+            .store(URL, true, &response(&[("cache-control", "max-age=600")]))
+            .await;
+
+        assert!(!dir.path().join("http").exists());
+    }
+
+    #[tokio::test]
+    async fn test_revalidated_response_is_fresh_with_updated_headers() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = HttpCache::new(dir.path(), CacheMode::Normal);
+        cache
+            .store(URL, true, &response(&[("etag", "\"v1\"")]))
+            .await;
+        let CacheLookup::Stale(stale) = cache.lookup(URL, true).await else {
+            panic!("expected a stale response");
+        };
+        let mut not_modified = HttpResponse::new(URL, 304, "text/html", b"");
+        not_modified
+            .headers
+            .insert(CACHE_CONTROL, "max-age=600".parse().unwrap());
+
+        let revalidated = cache.revalidated(URL, true, stale, &not_modified).await;
+
+        assert_eq!(revalidated.status, StatusCode::OK);
+        assert_eq!(revalidated.body, b"<p>cached</p>");
+        assert!(matches!(
+            cache.lookup(URL, true).await,
+            CacheLookup::Fresh(_)
+        ));
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_concurrent_stores_of_one_url_stay_consistent() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = std::sync::Arc::new(HttpCache::new(dir.path(), CacheMode::Normal));
+        let version = |n: usize| {
+            let mut response = response(&[("cache-control", "max-age=600")]);
+            response.body = format!("<p>version {}</p>", n).repeat(1000).into_bytes();
+            response
+                .headers
+                .insert("x-version", n.to_string().parse().unwrap());
+            response
+        };
+
+        for _ in 0..20 {
+            let writers: Vec<_> = (0..2)
+                .map(|n| {
+                    let cache = cache.clone();
+                    let response = version(n);
+                    tokio::spawn(async move { cache.store(URL, true, &response).await })
+                })
+                .collect();
+            for writer in writers {
+                writer.await.unwrap();
+            }
+
+            let CacheLookup::Fresh(stored) = cache.lookup(URL, true).await else {
+                panic!("expected a fresh response");
+            };
+            let n: usize = stored.headers["x-version"]
+                .to_str()
+                .unwrap()
+                .parse()
+                .unwrap();
+            assert_eq!(stored.body, version(n).body);
+        }
+        let leftovers = std::fs::read_dir(dir.path())
+            .unwrap()
+            .filter(|entry| {
+                entry
+                    .as_ref()
+                    .unwrap()
+                    .file_name()
+                    .to_string_lossy()
+                    .ends_with(".tmp")
+            })
+            .count();
+        assert_eq!(leftovers, 0);
+    }
+
+    #[test]
+    fn test_validators_use_etag_and_last_modified() {
+        let stored = response(&[
+            ("etag", "W/\"abc\""),
+            ("last-modified", "Wed, 21 Oct 2015 07:28:00 GMT"),
+        ]);
+
+        let headers = validators(&stored);
+
+        assert_eq!(headers[IF_NONE_MATCH], "W/\"abc\"");
+        assert_eq!(headers[IF_MODIFIED_SINCE], "Wed, 21 Oct 2015 07:28:00 GMT");
+    }
+}
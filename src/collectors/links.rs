@@ -0,0 +1,210 @@
+use std::collections::HashMap;
+
+use reqwest::Url;
+use scraper::{ElementRef, Html, Selector};
+use serde::{Deserialize, Serialize};
+
+use super::readability::normalize_whitespace;
+
+/// A hyperlink found on a page
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Link {
+    /// Absolute URL of the link target, without fragment
+    pub url: String,
+    /// Anchor text (or the link's title / image alt text when it has no text)
+    pub text: String,
+}
+
+/// Extract the deduplicated outbound links of a page in document order.
+///
+/// Targets are resolved against `<base href>` if present, else against `page_url`.
+/// Only http(s) links are kept; fragments are dropped and links back to the page
+/// itself are skipped.
+pub(crate) fn extract_links(document: &Html, page_url: &str) -> Vec<Link> {
+    let (Ok(page_url), Some(base_url)) =
+        (Url::parse(page_url), document_base_url(document, page_url))
+    else {
+        return Vec::new();
+    };
+
+    let selector = Selector::parse("a[href]").unwrap();
+    let mut links: Vec<Link> = Vec::new();
+    let mut index_by_url: HashMap<String, usize> = HashMap::new();
+
+    for anchor in document.select(&selector) {
+        let Some(url) = anchor
+            .attr("href")
+            .and_then(|href| resolve(&base_url, href))
+        else {
+            continue;
+        };
+        if same_document(&url, &page_url) {
+            continue;
+        }
+
+        let text = anchor_text(anchor);
+        let url = url.to_string();
+        match index_by_url.get(&url) {
+            // Keep the first occurrence, but take the text of a later one if it had none
+            Some(&i) => {
+                if links[i].text.is_empty() {
+                    links[i].text = text;
+                }
+            }
+            None => {
+                index_by_url.insert(url.clone(), links.len());
+                links.push(Link { url, text });
+            }
+        }
+    }
+    links
+}
+
+/// URL that relative links of the page resolve against: `<base href>` if present,
+/// else `page_url`
+pub(crate) fn document_base_url(document: &Html, page_url: &str) -> Option<Url> {
+    let page_url = Url::parse(page_url).ok()?;
+    let base_url = base_href(document).and_then(|href| page_url.join(&href).ok());
+    Some(base_url.unwrap_or(page_url))
+}
+
+fn base_href(document: &Html) -> Option<String> {
+    let selector = Selector::parse("base[href]").unwrap();
+    document
+        .select(&selector)
+        .next()
+        .and_then(|base| base.attr("href"))
+        .map(|href| href.trim().to_string())
+}
+
+/// Resolve an href to an absolute http(s) URL without fragment
+fn resolve(base_url: &Url, href: &str) -> Option<Url> {
+    let href = href.trim();
+    if href.is_empty() || href.starts_with('#') {
+        return None;
+    }
+    let mut url = base_url.join(href).ok()?;
+    if !matches!(url.scheme(), "http" | "https") {
+        return None;
+    }
+    url.set_fragment(None);
+    Some(url)
+}
+
+fn same_document(url: &Url, page_url: &Url) -> bool {
+    let mut page_url = page_url.clone();
+    page_url.set_fragment(None);
+    *url == page_url
+}
+
+fn anchor_text(anchor: ElementRef) -> String {
+    let text = normalize_whitespace(&anchor.text().collect::<String>());
+    if !text.is_empty() {
+        return text;
+    }
+
+    let img_selector = Selector::parse("img[alt]").unwrap();
+    anchor
+        .attr("title")
+        .or_else(|| anchor.attr("aria-label"))
+        .or_else(|| anchor.select(&img_selector).find_map(|img| img.attr("alt")))
+        .map(normalize_whitespace)
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn extract(html: &str) -> Vec<Link> {
+        extract_links(
+            &Html::parse_document(html),
+            "https://example.com/docs/index.html",
+        )
+    }
+
+    fn link(url: &str, text: &str) -> Link {
+        Link {
+            url: url.to_string(),
+            text: text.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_resolves_relative_links_against_page_url() {
+        let html = r#"
+            <a href="intro.html">Intro</a>
+            <a href="/about">About   us</a>
+            <a href="https://other.org/page">Other site</a>
+        "#;
+
+        assert_eq!(
+            extract(html),
+            vec![
+                link("https://example.com/docs/intro.html", "Intro"),
+                link("https://example.com/about", "About us"),
+                link("https://other.org/page", "Other site"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_resolves_against_base_href() {
+        let html = r#"
+            <head><base href="https://cdn.example.com/v2/"></head>
+            <body><a href="guide">Guide</a></body>
+        "#;
+
+        assert_eq!(
+            extract(html),
+            vec![link("https://cdn.example.com/v2/guide", "Guide")]
+        );
+    }
+
+    #[test]
+    fn test_deduplicates_and_drops_fragments() {
+        let html = r##"
+            <a href="/a#top"><img src="a.png"></a>
+            <a href="/a#bottom">Page A</a>
+            <a href="/b">Page B</a>
+            <a href="/b">Page B again</a>
+        "##;
+
+        assert_eq!(
+            extract(html),
+            vec![
+                link("https://example.com/a", "Page A"),
+                link("https://example.com/b", "Page B"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_skips_non_http_and_self_links() {
+        let html = r##"
+            <a href="#section">Jump</a>
+            <a href="index.html#faq">FAQ</a>
+            <a href="mailto:me@example.com">Mail</a>
+            <a href="javascript:void(0)">Script</a>
+            <a href="ftp://example.com/file">FTP</a>
+        "##;
+
+        assert!(extract(html).is_empty());
+    }
+
+    #[test]
+    fn test_uses_title_or_image_alt_when_anchor_has_no_text() {
+        let html = r#"
+            <a href="/x" title="X page"></a>
+            <a href="/y"><img src="y.png" alt="Y logo"></a>
+        "#;
+
+        assert_eq!(
+            extract(html),
+            vec![
+                link("https://example.com/x", "X page"),
+                link("https://example.com/y", "Y logo"),
+            ]
+        );
+    }
+}
@@ -0,0 +1,348 @@
+use reqwest::Url;
+use scraper::{ElementRef, Node};
+
+use super::readability::{
+    is_boilerplate, normalize_whitespace, walk_children, walk_inline, BLOCK_TAGS,
+};
+
+/// Indentation added per nesting level of lists
+const LIST_INDENT: &str = "   ";
+
+/// Render the extracted content as Markdown, keeping headings, lists, tables,
+/// code blocks and links. Relative link targets are resolved against `base_url`
+/// (the document's base URL, see [`document_base_url`](super::links::document_base_url)).
+pub(crate) fn to_markdown(elements: &[ElementRef], base_url: Option<Url>) -> String {
+    let writer = MarkdownWriter { base_url };
+    let mut blocks = Vec::new();
+    for element in elements {
+        writer.block(*element, &mut blocks);
+    }
+    blocks.join("\n\n")
+}
+
+struct MarkdownWriter {
+    base_url: Option<Url>,
+}
+
+impl MarkdownWriter {
+    /// Append the Markdown blocks for a block-level element to `blocks`
+    fn block(&self, element: ElementRef, blocks: &mut Vec<String>) {
+        let name = element.value().name();
+        match name {
+            "h1" | "h2" | "h3" | "h4" | "h5" | "h6" => {
+                let level = name[1..].parse::<usize>().unwrap_or(1);
+                let text = normalize_whitespace(&self.inline(element));
+                if !text.is_empty() {
+                    blocks.push(format!("{} {}", "#".repeat(level), text));
+                }
+            }
+            "pre" => blocks.push(code_block(element)),
+            "ul" | "ol" => {
+                let list = self.list(element, 0);
+                if !list.is_empty() {
+                    blocks.push(list);
+                }
+            }
+            "table" => {
+                let table = self.table(element);
+                if !table.is_empty() {
+                    blocks.push(table);
+                }
+            }
+            "blockquote" => {
+                let mut inner = Vec::new();
+                self.children(element, &mut inner);
+                if !inner.is_empty() {
+                    let quoted = inner
+                        .join("\n\n")
+                        .lines()
+                        .map(|line| format!("> {}", line).trim_end().to_string())
+                        .collect::<Vec<_>>()
+                        .join("\n");
+                    blocks.push(quoted);
+                }
+            }
+            "hr" => blocks.push("---".to_string()),
+            _ => self.children(element, blocks),
+        }
+    }
+
+    /// Render the children of a block element, grouping inline runs into paragraphs
+    fn children(&self, element: ElementRef, blocks: &mut Vec<String>) {
+        walk_children(
+            element,
+            blocks,
+            |child, blocks| self.block(child, blocks),
+            |child| self.inline_element(child),
+        );
+    }
+
+    /// Render the children of `element` as inline Markdown
+    fn inline(&self, element: ElementRef) -> String {
+        walk_inline(element, |child| self.inline_element(child))
+    }
+
+    fn inline_element(&self, element: ElementRef) -> String {
+        match element.value().name() {
+            "a" => {
+                let text = self.inline(element);
+                match element.attr("href").and_then(|href| self.resolve(href)) {
+                    Some(href) if !text.trim().is_empty() => {
+                        surround(&text, "[", &format!("]({})", href))
+                    }
+                    _ => text,
+                }
+            }
+            "strong" | "b" => surround(&self.inline(element), "**", "**"),
+            "em" | "i" => surround(&self.inline(element), "*", "*"),
+            "del" | "s" => surround(&self.inline(element), "~~", "~~"),
+            "code" | "kbd" | "samp" => {
+                let code = element.text().collect::<String>();
+                surround(&code, "`", "`")
+            }
+            "img" => {
+                let alt = element.attr("alt").unwrap_or_default();
+                match element.attr("src").and_then(|src| self.resolve(src)) {
+                    Some(src) => format!("![{}]({})", alt, src),
+                    None => alt.to_string(),
+                }
+            }
+            "br" => " ".to_string(),
+            _ => self.inline(element),
+        }
+    }
+
+    /// Render a list with nested lists indented below their item
+    fn list(&self, element: ElementRef, depth: usize) -> String {
+        let ordered = element.value().name() == "ol";
+        let mut number = element
+            .attr("start")
+            .and_then(|s| s.parse::<usize>().ok())
+            .unwrap_or(1);
+        let indent = LIST_INDENT.repeat(depth);
+
+        let mut lines = Vec::new();
+        for item in element.child_elements() {
+            if item.value().name() != "li" || is_boilerplate(&item) {
+                continue;
+            }
+
+            let mut text = String::new();
+            let mut nested = Vec::new();
+            for child in item.children() {
+                match child.value() {
+                    Node::Text(t) => text.push_str(t),
+                    Node::Element(_) => {
+                        let Some(child) = ElementRef::wrap(child) else {
+                            continue;
+                        };
+                        if is_boilerplate(&child) {
+                            continue;
+                        }
+                        match child.value().name() {
+                            "ul" | "ol" => nested.push(self.list(child, depth + 1)),
+                            name if BLOCK_TAGS.contains(&name) => {
+                                text.push(' ');
+                                text.push_str(&self.inline(child));
+                                text.push(' ');
+                            }
+                            _ => text.push_str(&self.inline_element(child)),
+                        }
+                    }
+                    _ => {}
+                }
+            }
+
+            let marker = if ordered {
+                format!("{}.", number)
+            } else {
+                "-".to_string()
+            };
+            number += 1;
+            lines.push(format!(
+                "{}{} {}",
+                indent,
+                marker,
+                normalize_whitespace(&text)
+            ));
+            lines.extend(nested.into_iter().filter(|n| !n.is_empty()));
+        }
+        lines.join("\n")
+    }
+
+    /// Render a table as a GFM table, using the first row as the header
+    fn table(&self, element: ElementRef) -> String {
+        let rows = table_rows(element)
+            .into_iter()
+            .map(|row| {
+                row.child_elements()
+                    .filter(|c| matches!(c.value().name(), "td" | "th"))
+                    .map(|c| normalize_whitespace(&self.inline(c)).replace('|', "\\|"))
+                    .collect::<Vec<_>>()
+            })
+            .filter(|cells| !cells.is_empty())
+            .collect::<Vec<_>>();
+
+        let Some(columns) = rows.iter().map(Vec::len).max() else {
+            return String::new();
+        };
+
+        let format_row = |cells: &[String]| {
+            let mut padded = cells.to_vec();
+            padded.resize(columns, String::new());
+            format!("| {} |", padded.join(" | "))
+        };
+
+        let mut lines = vec![
+            format_row(&rows[0]),
+            format!("|{}", " --- |".repeat(columns)),
+        ];
+        lines.extend(rows[1..].iter().map(|row| format_row(row)));
+        lines.join("\n")
+    }
+
+    /// Resolve a link target against the document's base URL, dropping script links
+    fn resolve(&self, href: &str) -> Option<String> {
+        let href = href.trim();
+        if href.is_empty() || href.to_lowercase().starts_with("javascript:") {
+            return None;
+        }
+        match &self.base_url {
+            Some(base) => base.join(href).ok().map(|u| u.to_string()),
+            None => Some(href.to_string()),
+        }
+    }
+}
+
+/// Collect the rows of a table, without descending into nested tables
+fn table_rows(table: ElementRef) -> Vec<ElementRef> {
+    let mut rows = Vec::new();
+    for child in table.child_elements() {
+        match child.value().name() {
+            "tr" => rows.push(child),
+            "thead" | "tbody" | "tfoot" => {
+                rows.extend(child.child_elements().filter(|r| r.value().name() == "tr"))
+            }
+            _ => {}
+        }
+    }
+    rows
+}
+
+/// Render a `<pre>` block as a fenced code block with a language hint if one is declared
+fn code_block(pre: ElementRef) -> String {
+    let code = pre.text().collect::<String>();
+    let code = code.trim_matches('\n');
+    let language = std::iter::once(pre)
+        .chain(pre.child_elements().filter(|c| c.value().name() == "code"))
+        .filter_map(|e| e.attr("class"))
+        .flat_map(|class| class.split_whitespace())
+        .find_map(|class| {
+            class
+                .strip_prefix("language-")
+                .or_else(|| class.strip_prefix("lang-"))
+        })
+        .unwrap_or_default();
+    let fence = if code.contains("```") { "~~~" } else { "```" };
+
+    format!("{}{}\n{}\n{}", fence, language, code, fence)
+}
+
+/// Wrap inline content in Markdown markers, keeping surrounding whitespace outside them
+fn surround(text: &str, open: &str, close: &str) -> String {
+    let trimmed = text.trim();
+    if trimmed.is_empty() {
+        return text.to_string();
+    }
+    let leading = if text.starts_with(char::is_whitespace) {
+        " "
+    } else {
+        ""
+    };
+    let trailing = if text.ends_with(char::is_whitespace) {
+        " "
+    } else {
+        ""
+    };
+    format!("{}{}{}{}{}", leading, open, trimmed, close, trailing)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use scraper::Html;
+
+    fn convert(body: &str) -> String {
+        let html = format!("<html><body>{}</body></html>", body);
+        let document = Html::parse_document(&html);
+        let body_selector = scraper::Selector::parse("body").unwrap();
+        let body = document.select(&body_selector).next().unwrap();
+        to_markdown(
+            &[body],
+            Url::parse("https://example.com/docs/page.html").ok(),
+        )
+    }
+
+    #[test]
+    fn test_headings_keep_their_level() {
+        let markdown = convert("<h1>Title</h1><h3>Sub section</h3>");
+
+        assert_eq!(markdown, "# Title\n\n### Sub section");
+    }
+
+    #[test]
+    fn test_unordered_and_nested_ordered_lists() {
+        let markdown = convert(
+            "<ul><li>Apples</li><li>Fruits<ol start=\"3\"><li>Pear</li><li>Plum</li></ol></li></ul>",
+        );
+
+        assert_eq!(markdown, "- Apples\n- Fruits\n   3. Pear\n   4. Plum");
+    }
+
+    #[test]
+    fn test_table_becomes_gfm_table() {
+        let markdown = convert(
+            "<table><thead><tr><th>Flag</th><th>Meaning</th></tr></thead>\
+             <tbody><tr><td>-v</td><td>verbose | loud</td></tr><tr><td>-q</td></tr></tbody></table>",
+        );
+
+        assert_eq!(
+            markdown,
+            "| Flag | Meaning |\n| --- | --- |\n| -v | verbose \\| loud |\n| -q |  |"
+        );
+    }
+
+    #[test]
+    fn test_code_block_is_fenced_with_language() {
+        let markdown =
+            convert("<pre><code class=\"language-rust\">fn main() {\n    run();\n}</code></pre>");
+
+        assert_eq!(markdown, "```rust\nfn main() {\n    run();\n}\n```");
+    }
+
+    #[test]
+    fn test_links_are_resolved_against_page_url() {
+        let markdown = convert(
+            "<p>See <a href=\"../guide/\">the guide</a>, <a href=\"javascript:void(0)\">this</a> \
+             and <a href=\"https://rust-lang.org\">Rust</a>.</p>",
+        );
+
+        assert_eq!(
+            markdown,
+            "See [the guide](https://example.com/guide/), this and [Rust](https://rust-lang.org/)."
+        );
+    }
+
+    #[test]
+    fn test_inline_formatting_and_blockquote() {
+        let markdown = convert(
+            "<blockquote><p>Use <strong>bold</strong>, <em>italics</em> and <code>code</code></p>\
+             <p>Second line</p></blockquote>",
+        );
+
+        assert_eq!(
+            markdown,
+            "> Use **bold**, *italics* and `code`\n>\n> Second line"
+        );
+    }
+}
@@ -0,0 +1,323 @@
+use std::collections::HashMap;
+
+use reqwest::Url;
+use scraper::{Html, Selector};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::links::document_base_url;
+use super::readability::normalize_whitespace;
+
+/// JSON-LD `@type`s describing an article
+const ARTICLE_TYPES: &[&str] = &[
+    "Article",
+    "NewsArticle",
+    "ReportageNewsArticle",
+    "AnalysisNewsArticle",
+    "BlogPosting",
+    "TechArticle",
+    "ScholarlyArticle",
+    "Report",
+];
+
+/// Descriptive metadata of a web page, used to cite sources and judge freshness
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PageMetadata {
+    /// Preferred URL of the page (`<link rel="canonical">` or `og:url`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub canonical_url: Option<String>,
+    /// Title declared by OpenGraph, Twitter cards or JSON-LD
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub headline: Option<String>,
+    /// Short summary of the page
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// Author(s) of the page, comma separated
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub author: Option<String>,
+    /// Publication date/time as declared by the page (usually ISO 8601)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub published_time: Option<String>,
+    /// Last modification date/time as declared by the page
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub modified_time: Option<String>,
+    /// Name of the site or publisher
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub site_name: Option<String>,
+    /// Language of the page (e.g. "en", "ja-JP")
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub language: Option<String>,
+}
+
+impl PageMetadata {
+    /// Check whether no metadata was found
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+/// Extract page metadata from `<link>`/`<meta>` tags, JSON-LD and `<html lang>`.
+/// Relative URLs are resolved against the document's base URL (see
+/// [`document_base_url`]), which defaults to `page_url`.
+pub(crate) fn extract_metadata(document: &Html, page_url: &str) -> PageMetadata {
+    let base_url = document_base_url(document, page_url);
+    let meta = meta_tags(document);
+    let article = json_ld_article(document);
+    let meta_value = |keys: &[&str]| keys.iter().find_map(|key| meta.get(*key).cloned());
+    let article_str = |key: &str| article.as_ref().and_then(|a| json_text(&a[key]));
+
+    let canonical_url = select_attr(document, r#"link[rel~="canonical"]"#, "href")
+        .or_else(|| meta_value(&["og:url"]))
+        .or_else(|| article_str("url"))
+        .and_then(|u| resolve(base_url.as_ref(), &u));
+
+    let language = document
+        .root_element()
+        .attr("lang")
+        .map(str::trim)
+        .filter(|lang| !lang.is_empty())
+        .map(str::to_string)
+        .or_else(|| meta_value(&["content-language"]))
+        .or_else(|| meta_value(&["og:locale"]).map(|locale| locale.replace('_', "-")))
+        .or_else(|| article_str("inLanguage"));
+
+    PageMetadata {
+        canonical_url,
+        headline: meta_value(&["og:title", "twitter:title"]).or_else(|| article_str("headline")),
+        description: meta_value(&["description", "og:description", "twitter:description"])
+            .or_else(|| article_str("description")),
+        author: meta_value(&["author", "article:author"])
+            .or_else(|| article.as_ref().and_then(|a| json_names(&a["author"])))
+            .or_else(|| meta_value(&["twitter:creator"])),
+        published_time: meta_value(&["article:published_time", "og:published_time"])
+            .or_else(|| article_str("datePublished"))
+            .or_else(|| meta_value(&["pubdate", "date", "dc.date", "dcterms.created"])),
+        modified_time: meta_value(&["article:modified_time", "og:updated_time"])
+            .or_else(|| article_str("dateModified"))
+            .or_else(|| meta_value(&["dcterms.modified"])),
+        site_name: meta_value(&["og:site_name", "application-name"])
+            .or_else(|| article.as_ref().and_then(|a| json_names(&a["publisher"]))),
+        language,
+    }
+}
+
+/// Collect `<meta>` tags keyed by their lowercased `property`, `name` or `http-equiv`.
+/// The first tag with a given key wins.
+fn meta_tags(document: &Html) -> HashMap<String, String> {
+    let selector = Selector::parse("meta[content]").unwrap();
+    let mut tags = HashMap::new();
+    for element in document.select(&selector) {
+        let Some(content) = element.attr("content").map(normalize_whitespace) else {
+            continue;
+        };
+        if content.is_empty() {
+            continue;
+        }
+        for key in ["property", "name", "http-equiv"] {
+            if let Some(key) = element.attr(key) {
+                tags.entry(key.trim().to_lowercase())
+                    .or_insert_with(|| content.clone());
+            }
+        }
+    }
+    tags
+}
+
+/// Find the first JSON-LD node describing an article, looking inside arrays and `@graph`
+fn json_ld_article(document: &Html) -> Option<Value> {
+    let selector = Selector::parse(r#"script[type="application/ld+json"]"#).unwrap();
+    document
+        .select(&selector)
+        .filter_map(|script| serde_json::from_str::<Value>(&script.text().collect::<String>()).ok())
+        .find_map(find_article)
+}
+
+fn find_article(value: Value) -> Option<Value> {
+    match value {
+        Value::Array(items) => items.into_iter().find_map(find_article),
+        Value::Object(ref object) => {
+            if object.get("@type").is_some_and(is_article) {
+                return Some(value);
+            }
+            object.get("@graph").cloned().and_then(find_article)
+        }
+        _ => None,
+    }
+}
+
+fn is_article(json_type: &Value) -> bool {
+    match json_type {
+        Value::String(t) => ARTICLE_TYPES.contains(&t.as_str()),
+        Value::Array(types) => types.iter().any(is_article),
+        _ => false,
+    }
+}
+
+/// A JSON-LD value as text, if it is a non-empty string
+fn json_text(value: &Value) -> Option<String> {
+    let text = normalize_whitespace(value.as_str()?);
+    (!text.is_empty()).then_some(text)
+}
+
+/// Names from a JSON-LD Person/Organization, a plain string, or a list of either
+fn json_names(value: &Value) -> Option<String> {
+    let names = match value {
+        Value::Array(items) => items.iter().filter_map(json_names).collect::<Vec<_>>(),
+        Value::Object(object) => object.get("name").and_then(json_text).into_iter().collect(),
+        other => json_text(other).into_iter().collect(),
+    };
+    (!names.is_empty()).then(|| names.join(", "))
+}
+
+fn select_attr(document: &Html, selector: &str, attr: &str) -> Option<String> {
+    let selector = Selector::parse(selector).unwrap();
+    document
+        .select(&selector)
+        .find_map(|e| e.attr(attr))
+        .map(|value| value.trim().to_string())
+        .filter(|value| !value.is_empty())
+}
+
+fn resolve(base_url: Option<&Url>, url: &str) -> Option<String> {
+    match base_url {
+        Some(base) => base.join(url).ok().map(|u| u.to_string()),
+        None => Some(url.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn extract(html: &str) -> PageMetadata {
+        extract_metadata(
+            &Html::parse_document(html),
+            "https://example.com/news/story?ref=home",
+        )
+    }
+
+    #[test]
+    fn test_extracts_meta_and_opengraph_tags() {
+        let html = r#"
+            <html lang="en-GB">
+            <head>
+                <link rel="canonical" href="/news/story">
+                <meta name="description" content="The council approved the plan.">
+                <meta name="author" content="Jane Reporter">
+                <meta property="og:title" content="Council approves transit plan">
+                <meta property="og:site_name" content="Example News">
+                <meta property="article:published_time" content="2025-03-04T09:30:00Z">
+                <meta property="article:modified_time" content="2025-03-05T10:00:00Z">
+            </head>
+            <body></body>
+            </html>
+        "#;
+
+        let metadata = extract(html);
+
+        assert_eq!(
+            metadata,
+            PageMetadata {
+                canonical_url: Some("https://example.com/news/story".to_string()),
+                headline: Some("Council approves transit plan".to_string()),
+                description: Some("The council approved the plan.".to_string()),
+                author: Some("Jane Reporter".to_string()),
+                published_time: Some("2025-03-04T09:30:00Z".to_string()),
+                modified_time: Some("2025-03-05T10:00:00Z".to_string()),
+                site_name: Some("Example News".to_string()),
+                language: Some("en-GB".to_string()),
+            }
+        );
+    }
+
+    #[test]
+    fn test_canonical_url_is_resolved_against_base_href() {
+        let html = r#"
+            <html>
+            <head>
+                <base href="https://cdn.example.org/archive/">
+                <link rel="canonical" href="2025/story">
+            </head>
+            <body></body>
+            </html>
+        "#;
+
+        let metadata = extract(html);
+
+        assert_eq!(
+            metadata.canonical_url.as_deref(),
+            Some("https://cdn.example.org/archive/2025/story")
+        );
+    }
+
+    #[test]
+    fn test_falls_back_to_json_ld_article() {
+        let html = r#"
+            <html>
+            <head>
+                <script type="application/ld+json">
+                {
+                    "@context": "https://schema.org",
+                    "@graph": [
+                        {"@type": "WebSite", "name": "Ignored"},
+                        {
+                            "@type": "NewsArticle",
+                            "headline": "Rates held steady",
+                            "datePublished": "2024-12-01",
+                            "dateModified": "2024-12-02",
+                            "inLanguage": "ja",
+                            "author": [{"@type": "Person", "name": "A. Writer"}, "B. Editor"],
+                            "publisher": {"@type": "Organization", "name": "Daily Example"}
+                        }
+                    ]
+                }
+                </script>
+            </head>
+            <body></body>
+            </html>
+        "#;
+
+        let metadata = extract(html);
+
+        assert_eq!(metadata.headline, Some("Rates held steady".to_string()));
+        assert_eq!(metadata.published_time, Some("2024-12-01".to_string()));
+        assert_eq!(metadata.modified_time, Some("2024-12-02".to_string()));
+        assert_eq!(metadata.author, Some("A. Writer, B. Editor".to_string()));
+        assert_eq!(metadata.site_name, Some("Daily Example".to_string()));
+        assert_eq!(metadata.language, Some("ja".to_string()));
+    }
+
+    #[test]
+    fn test_twitter_tags_and_og_locale() {
+        let html = r#"
+            <html>
+            <head>
+                <meta name="twitter:title" content="Card title">
+                <meta name="twitter:description" content="Card description">
+                <meta name="twitter:creator" content="@someone">
+                <meta property="og:locale" content="ja_JP">
+                <meta property="og:url" content="https://example.com/canonical">
+            </head>
+            </html>
+        "#;
+
+        let metadata = extract(html);
+
+        assert_eq!(metadata.headline, Some("Card title".to_string()));
+        assert_eq!(metadata.description, Some("Card description".to_string()));
+        assert_eq!(metadata.author, Some("@someone".to_string()));
+        assert_eq!(metadata.language, Some("ja-JP".to_string()));
+        assert_eq!(
+            metadata.canonical_url,
+            Some("https://example.com/canonical".to_string())
+        );
+    }
+
+    #[test]
+    fn test_invalid_json_ld_is_ignored() {
+        let html =
+            r#"<html><head><script type="application/ld+json">{not json</script></head></html>"#;
+
+        assert!(extract(html).is_empty());
+    }
+}
@@ -1,3 +1,17 @@
+pub mod crawler;
+pub mod domain_policy;
+mod encoding;
+pub mod http_cache;
+pub mod links;
+mod markdown;
+pub mod metadata;
+pub mod opt_out;
+pub mod passages;
 pub mod pdf;
+pub mod politeness;
+mod readability;
+pub mod retry;
 pub mod robots;
+pub mod search;
+pub mod url_policy;
 pub mod web;
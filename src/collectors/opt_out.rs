@@ -0,0 +1,182 @@
+use std::str::FromStr;
+
+use reqwest::header::HeaderMap;
+use scraper::{Html, Selector};
+
+use super::web::USER_AGENT;
+
+/// Directives by which a publisher opts out of automated use of a page.
+/// `noimageai` is not listed: it only concerns images, and only text is extracted.
+const OPT_OUT_DIRECTIVES: &[&str] = &["noai", "noindex", "none"];
+
+/// Directives whose value contains a colon, so they are not mistaken for a user agent
+const DIRECTIVES_WITH_VALUE: &[&str] = &[
+    "unavailable_after",
+    "max-snippet",
+    "max-image-preview",
+    "max-video-preview",
+];
+
+/// What to do when a page opts out via `X-Robots-Tag` or `<meta name="robots">`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum OptOutPolicy {
+    /// Refuse to return the page
+    #[default]
+    Enforce,
+    /// Return the page, but log a warning
+    Warn,
+    /// Don't look at these directives
+    Ignore,
+}
+
+impl FromStr for OptOutPolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "enforce" => Ok(Self::Enforce),
+            "warn" => Ok(Self::Warn),
+            "ignore" => Ok(Self::Ignore),
+            other => Err(format!(
+                "unknown opt-out policy \"{}\" (expected enforce, warn or ignore)",
+                other
+            )),
+        }
+    }
+}
+
+/// Directives of the `X-Robots-Tag` headers that apply to us: unscoped ones and
+/// ones scoped to our user agent (e.g. `cipherant: noai`), lowercased
+pub(crate) fn header_directives(headers: &HeaderMap) -> Vec<String> {
+    headers
+        .get_all("x-robots-tag")
+        .into_iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| {
+            match value.split_once(':') {
+                Some((agent, directives)) if is_user_agent(agent) => {
+                    applies_to_us(agent).then_some(directives)
+                }
+                _ => Some(value),
+            }
+            .map(split_directives)
+            .unwrap_or_default()
+        })
+        .collect()
+}
+
+/// Directives of the `<meta name="robots">` tags and of the tags named after our
+/// user agent (e.g. `<meta name="cipherant">`), lowercased
+pub(crate) fn meta_directives(document: &Html) -> Vec<String> {
+    let selector = Selector::parse("meta[name][content]").unwrap();
+    document
+        .select(&selector)
+        .filter(|meta| {
+            let name = meta.attr("name").unwrap_or_default().trim();
+            name.eq_ignore_ascii_case("robots") || applies_to_us(name)
+        })
+        .flat_map(|meta| split_directives(meta.attr("content").unwrap_or_default()))
+        .collect()
+}
+
+/// The opt-out directives among `directives`
+pub(crate) fn opt_outs(directives: &[String]) -> Vec<&str> {
+    directives
+        .iter()
+        .map(String::as_str)
+        .filter(|directive| OPT_OUT_DIRECTIVES.contains(directive))
+        .collect()
+}
+
+fn split_directives(directives: &str) -> Vec<String> {
+    directives
+        .split(',')
+        .map(|directive| directive.trim().to_ascii_lowercase())
+        .filter(|directive| !directive.is_empty())
+        .collect()
+}
+
+/// Whether the text before a colon in `X-Robots-Tag` names a user agent
+/// rather than a directive such as `unavailable_after`
+fn is_user_agent(token: &str) -> bool {
+    let token = token.trim();
+    !token.is_empty()
+        && !token.contains([',', ' '])
+        && !DIRECTIVES_WITH_VALUE.contains(&token.to_ascii_lowercase().as_str())
+}
+
+fn applies_to_us(agent: &str) -> bool {
+    let product = USER_AGENT.split('/').next().unwrap_or(USER_AGENT);
+    agent.trim().eq_ignore_ascii_case(product)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn headers(values: &[&str]) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        for value in values {
+            headers.append("x-robots-tag", value.parse().unwrap());
+        }
+        headers
+    }
+
+    #[test]
+    fn test_header_directives_unscoped_and_scoped_to_us() {
+        let headers = headers(&[
+            "noindex, NoFollow",
+            "cipherant: noai",
+            "googlebot: noimageai",
+        ]);
+
+        assert_eq!(
+            header_directives(&headers),
+            vec!["noindex", "nofollow", "noai"]
+        );
+    }
+
+    #[test]
+    fn test_header_directives_with_values_are_not_user_agents() {
+        let headers = headers(&["unavailable_after: 25 Jun 2010 15:00:00 PST"]);
+
+        assert_eq!(
+            header_directives(&headers),
+            vec!["unavailable_after: 25 jun 2010 15:00:00 pst"]
+        );
+    }
+
+    #[test]
+    fn test_meta_directives_from_robots_and_our_user_agent() {
+        let document = Html::parse_document(
+            r#"<html><head>
+                <meta name="robots" content="noai, noimageai">
+                <meta name="Cipherant" content="noindex">
+                <meta name="googlebot" content="none">
+            </head></html>"#,
+        );
+
+        assert_eq!(
+            meta_directives(&document),
+            vec!["noai", "noimageai", "noindex"]
+        );
+    }
+
+    #[test]
+    fn test_opt_outs() {
+        let directives = vec![
+            "noimageai".to_string(),
+            "nofollow".to_string(),
+            "noai".to_string(),
+            "none".to_string(),
+        ];
+
+        assert_eq!(opt_outs(&directives), vec!["noai", "none"]);
+    }
+
+    #[test]
+    fn test_parse_policy() {
+        assert_eq!("Warn".parse::<OptOutPolicy>(), Ok(OptOutPolicy::Warn));
+        assert!("sometimes".parse::<OptOutPolicy>().is_err());
+    }
+}
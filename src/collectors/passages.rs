@@ -0,0 +1,499 @@
+use std::collections::HashSet;
+
+use serde::Serialize;
+
+/// Target size of a chunk, in characters
+const CHUNK_CHARS: usize = 600;
+
+/// A piece of a document
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Passage {
+    /// Page the passage is on, for paged documents (PDF)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub page: Option<u32>,
+    /// Offset of the passage in the document (in its page, if paged), in characters
+    pub offset: usize,
+    pub text: String,
+}
+
+/// Split `text` into chunks of about [`CHUNK_CHARS`] characters, keeping paragraphs
+/// (lines) together where they fit and cutting longer ones between words
+pub fn chunk(text: &str) -> Vec<Passage> {
+    let mut chunks = Vec::new();
+    // Byte range of the chunk being built
+    let mut current: Option<(usize, usize)> = None;
+
+    for (start, end) in paragraphs(text) {
+        for (start, end) in split_long(text, start, end) {
+            current = match current {
+                Some((chunk_start, _)) if text[chunk_start..end].chars().count() <= CHUNK_CHARS => {
+                    Some((chunk_start, end))
+                }
+                Some(range) => {
+                    chunks.push(range);
+                    Some((start, end))
+                }
+                None => Some((start, end)),
+            };
+        }
+    }
+    chunks.extend(current);
+
+    chunks
+        .into_iter()
+        .map(|(start, end)| Passage {
+            page: None,
+            offset: text[..start].chars().count(),
+            text: text[start..end].to_string(),
+        })
+        .collect()
+}
+
+/// Chunk each page on its own, so that every passage has a page
+pub fn chunk_pages(pages: &[(u32, &str)]) -> Vec<Passage> {
+    pages
+        .iter()
+        .flat_map(|(number, text)| {
+            chunk(text).into_iter().map(|passage| Passage {
+                page: Some(*number),
+                ..passage
+            })
+        })
+        .collect()
+}
+
+/// Byte ranges of the non-blank lines of `text`, trimmed
+fn paragraphs(text: &str) -> Vec<(usize, usize)> {
+    let mut ranges = Vec::new();
+    let mut line_start = 0;
+    for line in text.split_inclusive('\n') {
+        let trimmed = line.trim();
+        if !trimmed.is_empty() {
+            let start = line_start + (line.len() - line.trim_start().len());
+            ranges.push((start, start + trimmed.len()));
+        }
+        line_start += line.len();
+    }
+    ranges
+}
+
+/// Cut the byte range `start..end` of `text` into pieces of at most [`CHUNK_CHARS`]
+/// characters, between words where possible
+fn split_long(text: &str, mut start: usize, end: usize) -> Vec<(usize, usize)> {
+    let mut pieces = Vec::new();
+    while text[start..end].chars().count() > CHUNK_CHARS {
+        let limit = start
+            + text[start..end]
+                .char_indices()
+                .nth(CHUNK_CHARS)
+                .map_or(end - start, |(index, _)| index);
+        let cut = text[start..limit]
+            .rfind(char::is_whitespace)
+            .filter(|&index| index > 0)
+            .map_or(limit, |index| start + index);
+        pieces.push((start, cut));
+        start = cut + (text[cut..].len() - text[cut..].trim_start().len());
+    }
+    if start < end {
+        pieces.push((start, end));
+    }
+    pieces
+}
+
+/// Lowercase words of `text`, the unit passages are matched on
+fn terms(text: &str) -> impl Iterator<Item = String> + '_ {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(str::to_lowercase)
+}
+
+/// BM25 scores of `chunks` for `query`, with the chunks themselves as the corpus:
+/// rare query terms weigh more than ones found everywhere, and matches in long
+/// chunks less than in short ones
+fn bm25(chunks: &[Passage], query: &str) -> Vec<f64> {
+    const K1: f64 = 1.2;
+    const B: f64 = 0.75;
+
+    let query_terms: HashSet<String> = terms(query).collect();
+    let chunk_terms: Vec<Vec<String>> = chunks
+        .iter()
+        .map(|chunk| terms(&chunk.text).collect())
+        .collect();
+    let count = chunks.len() as f64;
+    let average_len = chunk_terms.iter().map(Vec::len).sum::<usize>() as f64 / count.max(1.0);
+
+    let weights: Vec<(&String, f64)> = query_terms
+        .iter()
+        .map(|term| {
+            let frequency = chunk_terms
+                .iter()
+                .filter(|terms| terms.contains(term))
+                .count() as f64;
+            let idf = (1.0 + (count - frequency + 0.5) / (frequency + 0.5)).ln();
+            (term, idf)
+        })
+        .collect();
+
+    chunk_terms
+        .iter()
+        .map(|terms| {
+            let len_norm = 1.0 - B + B * terms.len() as f64 / average_len.max(1.0);
+            weights
+                .iter()
+                .map(|(term, idf)| {
+                    let tf = terms.iter().filter(|t| t == term).count() as f64;
+                    idf * tf * (K1 + 1.0) / (tf + K1 * len_norm)
+                })
+                .sum()
+        })
+        .collect()
+}
+
+/// The passages of `text` that best match `query` (by BM25), in document order,
+/// totalling at most `max_chars` characters. Without any match, the beginning of
+/// the text. The flag tells whether the only passage was cut to fit the budget.
+pub fn select_passages(text: &str, query: &str, max_chars: usize) -> (Vec<Passage>, bool) {
+    select(chunk(text), query, max_chars)
+}
+
+/// Like [`select_passages`] for a paged document: passages don't cross pages and
+/// carry their page number
+pub fn select_paged_passages(
+    pages: &[(u32, &str)],
+    query: &str,
+    max_chars: usize,
+) -> (Vec<Passage>, bool) {
+    select(chunk_pages(pages), query, max_chars)
+}
+
+/// The best chunks for `query` within `max_chars` characters, in document order.
+/// When not even one chunk fits, the best one is cut to the budget, which the
+/// returned flag tells.
+fn select(chunks: Vec<Passage>, query: &str, max_chars: usize) -> (Vec<Passage>, bool) {
+    if chunks.is_empty() {
+        return (Vec::new(), false);
+    }
+    let mut ranked: Vec<(usize, f64)> = bm25(&chunks, query)
+        .into_iter()
+        .enumerate()
+        .filter(|(_, score)| *score > 0.0)
+        .collect();
+    if ranked.is_empty() {
+        ranked = (0..chunks.len()).map(|index| (index, 0.0)).collect();
+    }
+    // Stable sort: equally good chunks keep document order
+    ranked.sort_by(|a, b| b.1.total_cmp(&a.1));
+
+    let mut selected = Vec::new();
+    let mut budget = max_chars;
+    for &(index, _) in &ranked {
+        let size = chunks[index].text.chars().count();
+        if size <= budget {
+            budget -= size;
+            selected.push(index);
+        }
+    }
+    let mut chunks: Vec<Option<Passage>> = chunks.into_iter().map(Some).collect();
+    if selected.is_empty() {
+        let Some(best) = ranked.first().and_then(|&(index, _)| chunks[index].take()) else {
+            return (Vec::new(), false);
+        };
+        let size = best.text.len();
+        let best = cut(best, max_chars);
+        let truncated = best
+            .as_ref()
+            .is_none_or(|passage| passage.text.len() < size);
+        return (best.into_iter().collect(), truncated);
+    }
+    selected.sort_unstable();
+    let passages = selected
+        .into_iter()
+        .filter_map(|index| chunks[index].take())
+        .collect();
+    (passages, false)
+}
+
+/// The beginning of `passage`, at most `max_chars` characters long and cut between
+/// words where possible; `None` if nothing is left
+fn cut(mut passage: Passage, max_chars: usize) -> Option<Passage> {
+    if let Some((limit, _)) = passage.text.char_indices().nth(max_chars) {
+        let end = passage.text[..limit]
+            .rfind(char::is_whitespace)
+            .filter(|&index| index > 0)
+            .unwrap_or(limit);
+        passage.text.truncate(end);
+        passage.text.truncate(passage.text.trim_end().len());
+    }
+    (!passage.text.is_empty()).then_some(passage)
+}
+
+/// Rough number of characters per token, to turn a token budget into characters
+const CHARS_PER_TOKEN: usize = 4;
+
+/// Passages returned when a query is given without a budget, in characters
+const DEFAULT_BUDGET_CHARS: usize = 4000;
+
+/// The part of a document a tool returns
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct Excerpt {
+    /// The text, or its beginning if it was over budget; empty when `passages` is set
+    pub content: String,
+    /// The passages matching the query, if one was given
+    pub passages: Option<Vec<Passage>>,
+    /// Whether `content`, or the only passage, was cut to fit the budget
+    pub truncated: bool,
+}
+
+/// Fit `text` into a budget of `max_chars` characters or `max_tokens` tokens
+/// (the smaller one wins): the best passages for `query` if one is given, else the
+/// beginning of the text. Without budget or query, the whole text.
+pub fn excerpt(
+    text: String,
+    query: Option<&str>,
+    max_chars: Option<usize>,
+    max_tokens: Option<usize>,
+) -> Excerpt {
+    fit(text, chunk, query, budget(max_chars, max_tokens))
+}
+
+/// Like [`excerpt`] for a paged document whose `text` shows `pages`: passages
+/// don't cross pages and carry their page number
+pub fn paged_excerpt(
+    text: String,
+    pages: &[(u32, &str)],
+    query: Option<&str>,
+    max_chars: Option<usize>,
+    max_tokens: Option<usize>,
+) -> Excerpt {
+    fit(
+        text,
+        |_| chunk_pages(pages),
+        query,
+        budget(max_chars, max_tokens),
+    )
+}
+
+/// Budget in characters from a character and a token budget, the smaller one winning
+fn budget(max_chars: Option<usize>, max_tokens: Option<usize>) -> Option<usize> {
+    match (
+        max_chars,
+        max_tokens.map(|t| t.saturating_mul(CHARS_PER_TOKEN)),
+    ) {
+        (Some(chars), Some(tokens)) => Some(chars.min(tokens)),
+        (chars, tokens) => chars.or(tokens),
+    }
+}
+
+fn fit(
+    text: String,
+    chunker: impl FnOnce(&str) -> Vec<Passage>,
+    query: Option<&str>,
+    budget: Option<usize>,
+) -> Excerpt {
+    match (query, budget) {
+        (Some(query), budget) if !query.trim().is_empty() => {
+            let (passages, truncated) = select(
+                chunker(&text),
+                query,
+                budget.unwrap_or(DEFAULT_BUDGET_CHARS),
+            );
+            Excerpt {
+                content: String::new(),
+                passages: Some(passages),
+                truncated,
+            }
+        }
+        (_, Some(budget)) => {
+            let mut content = text;
+            let truncated = match content.char_indices().nth(budget) {
+                Some((end, _)) => {
+                    content.truncate(end);
+                    true
+                }
+                None => false,
+            };
+            Excerpt {
+                content,
+                passages: None,
+                truncated,
+            }
+        }
+        _ => Excerpt {
+            content: text,
+            ..Excerpt::default()
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_chunk_keeps_short_paragraphs_together() {
+        let text = "First paragraph.\n\nSecond paragraph.\n";
+
+        let chunks = chunk(text);
+
+        assert_eq!(
+            chunks,
+            vec![Passage {
+                page: None,
+                offset: 0,
+                text: "First paragraph.\n\nSecond paragraph.".to_string(),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_chunk_splits_long_text_between_words() {
+        let paragraph = "word ".repeat(300);
+        let text = format!("Intro é\n{}\nOutro", paragraph.trim_end());
+
+        let chunks = chunk(&text);
+
+        assert!(chunks.len() >= 3);
+        for chunk in &chunks {
+            assert!(chunk.text.chars().count() <= CHUNK_CHARS);
+            assert!(!chunk.text.starts_with(' ') && !chunk.text.ends_with(' '));
+            // Offsets count characters, so "é" counts once
+            let expected: String = text.chars().skip(chunk.offset).take(4).collect();
+            assert!(chunk.text.starts_with(&expected));
+        }
+        assert!(chunks.last().unwrap().text.ends_with("Outro"));
+    }
+
+    #[test]
+    fn test_select_passages_prefers_matching_chunks() {
+        let text = format!(
+            "{}\nTokio is an asynchronous runtime for Rust.\n{}",
+            "gardening ".repeat(100),
+            "cooking ".repeat(100)
+        );
+
+        let (passages, truncated) = select_passages(&text, "Which runtime does Tokio use?", 700);
+        assert!(!truncated);
+
+        assert_eq!(passages.len(), 1);
+        assert!(passages[0]
+            .text
+            .contains("Tokio is an asynchronous runtime for Rust."));
+        let at_offset: String = text.chars().skip(passages[0].offset).collect();
+        assert!(at_offset.starts_with(&passages[0].text));
+    }
+
+    #[test]
+    fn test_bm25_weighs_rare_terms_more() {
+        let chunks: Vec<Passage> = [
+            "rust rust rust compiler",
+            "rust borrowck",
+            "rust cargo",
+            "rust crates",
+        ]
+        .iter()
+        .map(|text| Passage {
+            page: None,
+            offset: 0,
+            text: text.to_string(),
+        })
+        .collect();
+
+        let scores = bm25(&chunks, "Rust borrowck");
+
+        let best = scores
+            .iter()
+            .enumerate()
+            .max_by(|a, b| a.1.total_cmp(b.1))
+            .unwrap()
+            .0;
+        assert_eq!(best, 1);
+        assert!(scores.iter().all(|score| *score > 0.0));
+        assert_eq!(bm25(&chunks, "python"), vec![0.0; 4]);
+    }
+
+    #[test]
+    fn test_select_passages_without_match_returns_beginning() {
+        let text = format!("{}\n{}", "alpha ".repeat(100), "beta ".repeat(100));
+
+        let (passages, _) = select_passages(&text, "gamma", 600);
+
+        assert_eq!(passages.len(), 1);
+        assert!(passages[0].text.starts_with("alpha"));
+    }
+
+    #[test]
+    fn test_excerpt_budgets() {
+        let text = "a".repeat(100);
+
+        let whole = excerpt(text.clone(), None, None, None);
+        assert_eq!(whole.content, text);
+        assert!(!whole.truncated);
+
+        let cut = excerpt(text.clone(), None, Some(50), Some(5));
+        assert_eq!(cut.content.len(), 20);
+        assert!(cut.truncated);
+        assert!(cut.passages.is_none());
+
+        let passages = excerpt(text, Some("a"), Some(100), None);
+        assert!(passages.content.is_empty());
+        assert_eq!(passages.passages.unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_empty_page_is_not_truncated() {
+        let empty = excerpt(String::new(), Some("tokio runtime"), Some(300), None);
+
+        assert_eq!(empty.passages, Some(vec![]));
+        assert!(!empty.truncated);
+    }
+
+    #[test]
+    fn test_budget_below_chunk_size_cuts_best_passage() {
+        let text = format!(
+            "{}\n{}",
+            "Unrelated gardening notes. ".repeat(22),
+            "Tokio is an asynchronous runtime for Rust. ".repeat(13)
+        );
+        assert!(chunk(&text).iter().all(|c| c.text.chars().count() > 300));
+
+        let (cut, truncated) = select_passages(&text, "tokio runtime", 300);
+        assert_eq!(cut.len(), 1);
+        assert!(truncated);
+
+        let by_chars = excerpt(text.clone(), Some("tokio runtime"), Some(300), None);
+        let passages = by_chars.passages.unwrap();
+        assert_eq!(passages.len(), 1);
+        assert!(passages[0]
+            .text
+            .starts_with("Tokio is an asynchronous runtime"));
+        assert!(passages[0].text.chars().count() <= 300);
+        assert!(!passages[0].text.ends_with(' '));
+        assert!(by_chars.truncated);
+
+        let by_tokens = excerpt(text, Some("tokio runtime"), None, Some(25));
+        let passages = by_tokens.passages.unwrap();
+        assert_eq!(passages.len(), 1);
+        assert!(passages[0].text.chars().count() <= 100);
+        assert!(by_tokens.truncated);
+    }
+
+    #[test]
+    fn test_paged_excerpt_passages_carry_pages() {
+        let pages = [
+            (4, "Introduction to the protocol."),
+            (
+                5,
+                "The handshake uses a nonce.\nThe nonce is 16 bytes long.",
+            ),
+        ];
+
+        let excerpt = paged_excerpt(String::new(), &pages, Some("nonce length"), Some(60), None);
+
+        let passages = excerpt.passages.unwrap();
+        assert_eq!(passages.len(), 1);
+        assert_eq!(passages[0].page, Some(5));
+        assert_eq!(passages[0].offset, 0);
+        let json = serde_json::to_value(&passages[0]).unwrap();
+        assert_eq!(json["page"], 5);
+    }
+}
@@ -1,17 +1,128 @@
+use std::fmt::Write;
+use std::ops::RangeInclusive;
 use std::path::Path;
+use std::str::FromStr;
 
 use anyhow::{bail, Result};
+use pdf_extract::{decode_text_string, output_doc_page, Document, PlainTextOutput};
 use serde::{Deserialize, Serialize};
 
 /// Represents extracted content from a PDF file
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PdfContent {
-    /// The file path of the PDF
+    /// The file path or URL of the PDF
     pub path: String,
     /// The title of the PDF (from metadata, if available)
     pub title: Option<String>,
-    /// The extracted text content
-    pub text: String,
+    /// The extracted text of each page, first page first
+    pub pages: Vec<String>,
+}
+
+impl PdfContent {
+    /// The pages selected by `ranges` (all pages without ranges), numbered from 1
+    ///
+    /// # Errors
+    /// Returns an error if `ranges` selects no page of the document.
+    pub fn select_pages(&self, ranges: Option<&PageRanges>) -> Result<Vec<(u32, &str)>> {
+        let pages: Vec<(u32, &str)> = number_pages(&self.pages)
+            .into_iter()
+            .filter(|(number, _)| ranges.is_none_or(|ranges| ranges.contains(*number)))
+            .collect();
+        if pages.is_empty() {
+            if let Some(ranges) = ranges {
+                bail!(
+                    "Pages {} are out of range: {} has {} pages",
+                    ranges,
+                    self.path,
+                    self.pages.len()
+                );
+            }
+        }
+        Ok(pages)
+    }
+}
+
+/// `pages` with their numbers, from 1
+pub fn number_pages(pages: &[String]) -> Vec<(u32, &str)> {
+    (1..).zip(pages.iter().map(String::as_str)).collect()
+}
+
+/// Text of `pages`, each preceded by a `--- Page N ---` marker line so that
+/// passages can be cited by page
+pub fn format_pages(pages: &[(u32, &str)]) -> String {
+    let mut text = String::new();
+    for (number, page) in pages {
+        let _ = writeln!(text, "--- Page {} ---", number);
+        text.push_str(page.trim());
+        text.push_str("\n\n");
+    }
+    text.truncate(text.trim_end().len());
+    text
+}
+
+/// A selection of pages such as `5-12` or `1-3,7,10-`, numbered from 1
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PageRanges(Vec<RangeInclusive<u32>>);
+
+impl PageRanges {
+    pub fn contains(&self, page: u32) -> bool {
+        self.0.iter().any(|range| range.contains(&page))
+    }
+}
+
+impl FromStr for PageRanges {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let parse = |page: &str| -> Result<u32, String> {
+            match page.trim().parse::<u32>() {
+                Ok(page) if page > 0 => Ok(page),
+                _ => Err(format!(
+                    "invalid page \"{}\" in \"{}\" (pages are numbered from 1)",
+                    page.trim(),
+                    s
+                )),
+            }
+        };
+        let ranges = s
+            .split(',')
+            .map(|range| match range.split_once('-') {
+                Some((start, "")) => Ok(parse(start)?..=u32::MAX),
+                Some((start, end)) => {
+                    let (start, end) = (parse(start)?, parse(end)?);
+                    if start > end {
+                        return Err(format!("invalid page range \"{}\"", range.trim()));
+                    }
+                    Ok(start..=end)
+                }
+                None => parse(range).map(|page| page..=page),
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Self(ranges))
+    }
+}
+
+impl std::fmt::Display for PageRanges {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let ranges: Vec<String> = self
+            .0
+            .iter()
+            .map(|range| match (*range.start(), *range.end()) {
+                (start, end) if start == end => start.to_string(),
+                (start, u32::MAX) => format!("{}-", start),
+                (start, end) => format!("{}-{}", start, end),
+            })
+            .collect();
+        f.write_str(&ranges.join(","))
+    }
+}
+
+impl<'de> Deserialize<'de> for PageRanges {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(serde::de::Error::custom)
+    }
 }
 
 /// Read a local PDF file and extract its text content.
@@ -23,14 +134,51 @@ pub(crate) fn read_pdf(path: &str) -> Result<PdfContent> {
         bail!("{} is not found. Check whether file exists.", path)
     }
 
-    let pdf_text = pdf_extract::extract_text(path)?;
+    let bytes = std::fs::read(path)?;
+    parse_pdf(path, &bytes)
+}
+
+/// Extract the title and the text of each page from PDF bytes.
+/// `source` is the file path or URL the bytes came from.
+///
+/// # Errors
+/// Returns an error if the bytes cannot be parsed as PDF.
+pub(crate) fn parse_pdf(source: &str, bytes: &[u8]) -> Result<PdfContent> {
+    let mut document = Document::load_mem(bytes)?;
+    if document.is_encrypted() {
+        // Many PDFs are encrypted with an empty user password just to set permissions
+        document.decrypt("")?;
+    }
+
+    let pages = document
+        .get_pages()
+        .into_keys()
+        .map(|number| {
+            let mut text = String::new();
+            output_doc_page(&document, &mut PlainTextOutput::new(&mut text), number)?;
+            Ok(text)
+        })
+        .collect::<Result<Vec<_>>>()?;
+
     Ok(PdfContent {
-        path: path.to_string(),
-        title: None,
-        text: pdf_text,
+        path: source.to_string(),
+        title: document_title(&document),
+        pages,
     })
 }
 
+/// Read the title from the document information dictionary
+fn document_title(document: &Document) -> Option<String> {
+    let info = document.trailer.get(b"Info").ok()?;
+    let info = match info.as_reference() {
+        Ok(id) => document.get_dictionary(id).ok()?,
+        Err(_) => info.as_dict().ok()?,
+    };
+    let title = decode_text_string(info.get(b"Title").ok()?).ok()?;
+    let title = title.trim();
+    (!title.is_empty()).then(|| title.to_string())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -41,4 +189,64 @@ mod tests {
         assert!(result.is_err());
         assert!(result.unwrap_err().to_string().contains("not found"));
     }
+
+    #[test]
+    fn test_read_pdf_extracts_title_and_text() {
+        let result = read_pdf("tests/fixtures/pdf/sample.pdf").unwrap();
+
+        assert_eq!(result.title, Some("Sample Report".to_string()));
+        assert!(result.pages[0].contains("Hello from the sample report."));
+        assert!(result
+            .pages
+            .last()
+            .unwrap()
+            .contains("The last page holds the conclusion."));
+    }
+
+    #[test]
+    fn test_page_ranges_parse() {
+        let ranges: PageRanges = "1-3, 7,10-".parse().unwrap();
+
+        assert!(ranges.contains(1) && ranges.contains(3) && ranges.contains(7));
+        assert!(!ranges.contains(4) && !ranges.contains(9));
+        assert!(ranges.contains(10) && ranges.contains(400));
+        assert_eq!(ranges.to_string(), "1-3,7,10-");
+        assert!("0".parse::<PageRanges>().is_err());
+        assert!("5-2".parse::<PageRanges>().is_err());
+        assert!("chapter 3".parse::<PageRanges>().is_err());
+    }
+
+    #[test]
+    fn test_select_pages() {
+        let pdf = PdfContent {
+            path: "spec.pdf".to_string(),
+            title: None,
+            pages: vec!["One".to_string(), "Two".to_string(), "Three".to_string()],
+        };
+
+        assert_eq!(pdf.select_pages(None).unwrap().len(), 3);
+        let ranges = "2-".parse().unwrap();
+        assert_eq!(
+            pdf.select_pages(Some(&ranges)).unwrap(),
+            vec![(2, "Two"), (3, "Three")]
+        );
+        let ranges = "5-12".parse().unwrap();
+        let error = pdf.select_pages(Some(&ranges)).unwrap_err();
+        assert!(error.to_string().contains("spec.pdf has 3 pages"));
+    }
+
+    #[test]
+    fn test_format_pages() {
+        assert_eq!(
+            format_pages(&[(2, "Two\n"), (3, "Three")]),
+            "--- Page 2 ---\nTwo\n\n--- Page 3 ---\nThree"
+        );
+    }
+
+    #[test]
+    fn test_parse_pdf_rejects_non_pdf_bytes() {
+        let result = parse_pdf("https://example.com/fake.pdf", b"<html>not a pdf</html>");
+
+        assert!(result.is_err());
+    }
 }
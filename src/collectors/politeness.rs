@@ -0,0 +1,228 @@
+use std::num::NonZeroUsize;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use log::debug;
+use lru::LruCache;
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+use tokio::time::Instant;
+
+use super::robots::extract_origin;
+
+/// How politely requests to a single host are spaced out
+#[derive(Debug, Clone)]
+pub struct PolitenessConfig {
+    /// Minimum time between the starts of two requests to the same origin
+    pub min_interval: Duration,
+    /// Upper bound on a robots.txt `Crawl-delay`, so that a huge value can't stall a tool
+    pub max_crawl_delay: Duration,
+    /// Maximum number of requests in flight to the same origin
+    pub max_concurrent_per_host: usize,
+}
+
+impl Default for PolitenessConfig {
+    fn default() -> Self {
+        Self {
+            min_interval: Duration::from_millis(500),
+            max_crawl_delay: Duration::from_secs(30),
+            max_concurrent_per_host: 2,
+        }
+    }
+}
+
+/// Number of origins whose scheduling state is kept by default
+const DEFAULT_CAPACITY: usize = 1024;
+
+/// Spaces out and limits concurrent requests per origin (scheme + host + port).
+/// Each request waits for a connection slot of its origin, then until the origin's
+/// interval (`Crawl-delay` if declared, else `min_interval`) has passed since the
+/// previous request started.
+///
+/// The state of the least recently contacted origins is evicted beyond its capacity.
+pub(crate) struct HostScheduler {
+    config: PolitenessConfig,
+    hosts: Mutex<LruCache<String, Arc<HostState>>>,
+}
+
+struct HostState {
+    connections: Arc<Semaphore>,
+    /// Earliest start time of the next request
+    next_request: Mutex<Option<Instant>>,
+}
+
+impl HostScheduler {
+    pub(crate) fn new(config: PolitenessConfig) -> Self {
+        Self::with_capacity(config, DEFAULT_CAPACITY)
+    }
+
+    /// Create a scheduler keeping the state of at most `capacity` origins
+    pub(crate) fn with_capacity(config: PolitenessConfig, capacity: usize) -> Self {
+        let capacity = NonZeroUsize::new(capacity).unwrap_or(NonZeroUsize::MIN);
+        Self {
+            config,
+            hosts: Mutex::new(LruCache::new(capacity)),
+        }
+    }
+
+    /// Wait until a request to `url` may be sent.
+    /// The returned permit holds a connection slot of the origin until dropped.
+    pub(crate) async fn acquire(
+        &self,
+        url: &str,
+        crawl_delay: Option<Duration>,
+    ) -> OwnedSemaphorePermit {
+        let origin = extract_origin(url).unwrap_or_default();
+        let host = self.host(&origin);
+
+        let permit = host
+            .connections
+            .clone()
+            .acquire_owned()
+            .await
+            .expect("host semaphore is never closed");
+
+        let interval = crawl_delay
+            .map(|delay| delay.min(self.config.max_crawl_delay))
+            .unwrap_or_default()
+            .max(self.config.min_interval);
+        let start = {
+            let mut next_request = host.next_request.lock().unwrap();
+            let start = next_request.map_or_else(Instant::now, |next| next.max(Instant::now()));
+            *next_request = Some(start + interval);
+            start
+        };
+
+        let wait = start.saturating_duration_since(Instant::now());
+        if !wait.is_zero() {
+            debug!("Waiting {:?} before requesting {}", wait, url);
+            tokio::time::sleep_until(start).await;
+        }
+        permit
+    }
+
+    fn host(&self, origin: &str) -> Arc<HostState> {
+        let mut hosts = self.hosts.lock().unwrap();
+        hosts
+            .get_or_insert(origin.to_string(), || {
+                Arc::new(HostState {
+                    connections: Arc::new(Semaphore::new(
+                        self.config.max_concurrent_per_host.max(1),
+                    )),
+                    next_request: Mutex::new(None),
+                })
+            })
+            .clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scheduler(min_interval_ms: u64, max_concurrent_per_host: usize) -> HostScheduler {
+        HostScheduler::new(PolitenessConfig {
+            min_interval: Duration::from_millis(min_interval_ms),
+            max_crawl_delay: Duration::from_secs(10),
+            max_concurrent_per_host,
+        })
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_requests_to_same_host_are_spaced_by_min_interval() {
+        let scheduler = scheduler(500, 4);
+        let started = Instant::now();
+
+        drop(scheduler.acquire("https://example.com/a", None).await);
+        drop(scheduler.acquire("https://example.com/b", None).await);
+        drop(scheduler.acquire("https://example.com/c", None).await);
+
+        assert_eq!(started.elapsed(), Duration::from_millis(1000));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_different_hosts_are_not_delayed() {
+        let scheduler = scheduler(500, 4);
+        let started = Instant::now();
+
+        drop(scheduler.acquire("https://example.com/a", None).await);
+        drop(scheduler.acquire("https://example.org/a", None).await);
+        drop(scheduler.acquire("http://example.com/a", None).await);
+
+        assert_eq!(started.elapsed(), Duration::ZERO);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_crawl_delay_overrides_shorter_min_interval_up_to_cap() {
+        let scheduler = scheduler(500, 4);
+        let started = Instant::now();
+
+        drop(
+            scheduler
+                .acquire("https://example.com/a", Some(Duration::from_secs(2)))
+                .await,
+        );
+        drop(
+            scheduler
+                .acquire("https://example.com/b", Some(Duration::from_secs(2)))
+                .await,
+        );
+        let after_crawl_delay = started.elapsed();
+        drop(
+            scheduler
+                .acquire("https://example.com/c", Some(Duration::from_secs(60)))
+                .await,
+        );
+        drop(scheduler.acquire("https://example.com/d", None).await);
+
+        assert_eq!(after_crawl_delay, Duration::from_secs(2));
+        // 60s is capped at 10s
+        assert_eq!(started.elapsed(), Duration::from_secs(14));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_concurrent_requests_per_host_are_capped() {
+        let scheduler = scheduler(0, 1);
+        let first = scheduler.acquire("https://example.com/a", None).await;
+
+        let blocked = tokio::time::timeout(
+            Duration::from_secs(60),
+            scheduler.acquire("https://example.com/b", None),
+        )
+        .await;
+        let other_host = tokio::time::timeout(
+            Duration::from_secs(60),
+            scheduler.acquire("https://example.org/b", None),
+        )
+        .await;
+        drop(first);
+        let unblocked = tokio::time::timeout(
+            Duration::from_secs(60),
+            scheduler.acquire("https://example.com/b", None),
+        )
+        .await;
+
+        assert!(blocked.is_err());
+        assert!(other_host.is_ok());
+        assert!(unblocked.is_ok());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_least_recently_contacted_hosts_are_evicted() {
+        let scheduler = HostScheduler::with_capacity(
+            PolitenessConfig {
+                min_interval: Duration::from_millis(500),
+                ..PolitenessConfig::default()
+            },
+            2,
+        );
+
+        drop(scheduler.acquire("https://a.example.com/", None).await);
+        drop(scheduler.acquire("https://b.example.com/", None).await);
+        drop(scheduler.acquire("https://c.example.com/", None).await);
+
+        let hosts = scheduler.hosts.lock().unwrap();
+        assert_eq!(hosts.len(), 2);
+        assert!(!hosts.contains("https://a.example.com"));
+        assert!(hosts.contains("https://c.example.com"));
+    }
+}
@@ -0,0 +1,571 @@
+use std::collections::HashMap;
+
+use ego_tree::NodeId;
+use scraper::{ElementRef, Html, Node};
+
+/// Elements that never hold article content and are dropped with their subtree.
+/// `form` isn't one: ASP.NET WebForms pages wrap their whole body in one.
+const BOILERPLATE_TAGS: &[&str] = &[
+    "script", "style", "noscript", "template", "nav", "aside", "footer", "iframe", "svg", "canvas",
+    "button", "input", "select", "textarea", "dialog", "object", "embed",
+];
+
+/// ARIA roles used for page chrome rather than content
+const BOILERPLATE_ROLES: &[&str] = &[
+    "navigation",
+    "banner",
+    "contentinfo",
+    "complementary",
+    "dialog",
+    "alertdialog",
+    "menu",
+    "menubar",
+    "search",
+];
+
+/// class/id fragments of elements that are unlikely to be content
+const UNLIKELY_HINTS: &[&str] = &[
+    "-ad-",
+    "banner",
+    "breadcrumb",
+    "combx",
+    "comment",
+    "community",
+    "consent",
+    "cookie",
+    "disqus",
+    "footer",
+    "gdpr",
+    "header",
+    "menu",
+    "modal",
+    "newsletter",
+    "pagination",
+    "pager",
+    "popup",
+    "promo",
+    "related",
+    "share",
+    "shoutbox",
+    "sidebar",
+    "social",
+    "sponsor",
+    "subscribe",
+    "toolbar",
+];
+
+/// class/id fragments that rescue an element matching `UNLIKELY_HINTS`
+const MAYBE_CONTENT_HINTS: &[&str] = &["and", "article", "body", "column", "content", "main"];
+
+/// class/id fragments that raise a candidate's score
+const POSITIVE_HINTS: &[&str] = &[
+    "article", "body", "content", "entry", "hentry", "main", "page", "post", "text", "blog",
+    "story",
+];
+
+/// class/id fragments that lower a candidate's score
+const NEGATIVE_HINTS: &[&str] = &[
+    "hidden",
+    "banner",
+    "combx",
+    "comment",
+    "com-",
+    "contact",
+    "foot",
+    "masthead",
+    "media",
+    "meta",
+    "outbrain",
+    "promo",
+    "related",
+    "scroll",
+    "share",
+    "shoutbox",
+    "sidebar",
+    "skyscraper",
+    "sponsor",
+    "shopping",
+    "tags",
+    "tool",
+    "widget",
+];
+
+/// Elements whose text is scored as a paragraph of content
+const SCORABLE_TAGS: &[&str] = &[
+    "p",
+    "pre",
+    "td",
+    "li",
+    "blockquote",
+    "h2",
+    "h3",
+    "h4",
+    "h5",
+    "h6",
+];
+
+/// Elements that start a new block of text
+pub(crate) const BLOCK_TAGS: &[&str] = &[
+    "address",
+    "article",
+    "blockquote",
+    "body",
+    "caption",
+    "dd",
+    "details",
+    "div",
+    "dl",
+    "dt",
+    "figcaption",
+    "figure",
+    "h1",
+    "h2",
+    "h3",
+    "h4",
+    "h5",
+    "h6",
+    "header",
+    "hr",
+    "li",
+    "main",
+    "ol",
+    "p",
+    "pre",
+    "section",
+    "summary",
+    "table",
+    "tbody",
+    "td",
+    "tfoot",
+    "th",
+    "thead",
+    "tr",
+    "ul",
+];
+
+/// Paragraphs shorter than this are ignored when scoring
+const MIN_PARAGRAPH_CHARS: usize = 25;
+
+/// Check whether an element is page chrome (navigation, ads, banners, scripts, ...)
+/// that should be dropped along with its subtree.
+pub(crate) fn is_boilerplate(element: &ElementRef) -> bool {
+    let value = element.value();
+    let name = value.name();
+
+    if BOILERPLATE_TAGS.contains(&name) {
+        return true;
+    }
+    if value.attr("hidden").is_some() || value.attr("aria-hidden") == Some("true") {
+        return true;
+    }
+    if let Some(role) = value.attr("role") {
+        if BOILERPLATE_ROLES.contains(&role) {
+            return true;
+        }
+    }
+    // A site-wide <header> is chrome, but an article's own header holds its headline
+    if name == "header" && !has_content_ancestor(element) {
+        return true;
+    }
+    if matches!(name, "html" | "body" | "article" | "main") {
+        return false;
+    }
+
+    let hints = class_and_id(element);
+    UNLIKELY_HINTS.iter().any(|h| hints.contains(h))
+        && !MAYBE_CONTENT_HINTS.iter().any(|h| hints.contains(h))
+}
+
+/// Find the element(s) holding the main content of the document.
+///
+/// Scores block-level text containers the way Mozilla Readability does and returns
+/// the best candidate together with any sibling that looks like part of the same article.
+/// Falls back to `<body>` when no candidate stands out.
+pub(crate) fn extract_main_content(document: &Html) -> Vec<ElementRef<'_>> {
+    let body = document
+        .root_element()
+        .child_elements()
+        .find(|e| e.value().name() == "body")
+        .unwrap_or_else(|| document.root_element());
+
+    let mut scores: HashMap<NodeId, f64> = HashMap::new();
+    walk_content(body, &mut |element| score_paragraph(element, &mut scores));
+
+    // Pick the highest scoring candidate, preferring the earliest one on ties
+    let mut best: Option<(ElementRef, f64)> = None;
+    walk_content(body, &mut |element| {
+        let Some(score) = final_score(&element, &scores) else {
+            return;
+        };
+        if best.is_none_or(|(_, best_score)| score > best_score) {
+            best = Some((element, score));
+        }
+    });
+
+    let Some((top, top_score)) = best else {
+        return vec![body];
+    };
+    if top == body || to_text(&[top]).is_empty() {
+        return vec![body];
+    }
+
+    let parent = match top.parent().and_then(ElementRef::wrap) {
+        Some(p) if p != document.root_element() => p,
+        _ => return vec![top],
+    };
+
+    let threshold = f64::max(10.0, top_score * 0.2);
+    parent
+        .child_elements()
+        .filter(|sibling| {
+            *sibling == top || (!is_boilerplate(sibling) && is_related(sibling, &scores, threshold))
+        })
+        .collect()
+}
+
+/// Render the extracted content as plain text, one block per paragraph,
+/// separated by blank lines.
+pub(crate) fn to_text(elements: &[ElementRef]) -> String {
+    let mut blocks = Vec::new();
+    for element in elements {
+        collect_element(*element, &mut blocks);
+    }
+    blocks.join("\n\n")
+}
+
+/// Visit every element under `root` (inclusive), skipping boilerplate subtrees
+fn walk_content<'a>(root: ElementRef<'a>, visit: &mut impl FnMut(ElementRef<'a>)) {
+    if is_boilerplate(&root) {
+        return;
+    }
+    visit(root);
+    for child in root.child_elements() {
+        walk_content(child, visit);
+    }
+}
+
+/// Score a paragraph-like element and propagate the score to its ancestors
+fn score_paragraph(element: ElementRef, scores: &mut HashMap<NodeId, f64>) {
+    if !is_scorable(&element) {
+        return;
+    }
+    let text = normalize_whitespace(&inline_text(element));
+    let length = text.chars().count();
+    if length < MIN_PARAGRAPH_CHARS {
+        return;
+    }
+
+    let commas = text.matches([',', '、', '，']).count();
+    let score = 1.0 + commas as f64 + f64::min(length as f64 / 100.0, 3.0);
+
+    let ancestors = element
+        .ancestors()
+        .filter_map(ElementRef::wrap)
+        .filter(|a| a.value().name() != "html")
+        .take(5);
+    for (level, ancestor) in ancestors.enumerate() {
+        let divider = match level {
+            0 => 1.0,
+            1 => 2.0,
+            n => n as f64 * 3.0,
+        };
+        *scores
+            .entry(ancestor.id())
+            .or_insert_with(|| initial_score(&ancestor)) += score / divider;
+    }
+}
+
+/// A div without block-level children is scored like a paragraph
+fn is_scorable(element: &ElementRef) -> bool {
+    let name = element.value().name();
+    SCORABLE_TAGS.contains(&name)
+        || (name == "div"
+            && !element
+                .child_elements()
+                .any(|c| BLOCK_TAGS.contains(&c.value().name())))
+}
+
+fn initial_score(element: &ElementRef) -> f64 {
+    let tag_score = match element.value().name() {
+        "div" | "article" | "main" => 5.0,
+        "pre" | "td" | "blockquote" => 3.0,
+        "address" | "ol" | "ul" | "dl" | "dd" | "dt" | "li" | "form" => -3.0,
+        "h1" | "h2" | "h3" | "h4" | "h5" | "h6" | "th" => -5.0,
+        _ => 0.0,
+    };
+    tag_score + class_weight(element)
+}
+
+fn class_weight(element: &ElementRef) -> f64 {
+    let hints = class_and_id(element);
+    let mut weight = 0.0;
+    if POSITIVE_HINTS.iter().any(|h| hints.contains(h)) {
+        weight += 25.0;
+    }
+    if NEGATIVE_HINTS.iter().any(|h| hints.contains(h)) {
+        weight -= 25.0;
+    }
+    weight
+}
+
+/// Candidate score scaled down by how much of its text is link text
+fn final_score(element: &ElementRef, scores: &HashMap<NodeId, f64>) -> Option<f64> {
+    scores
+        .get(&element.id())
+        .map(|score| score * (1.0 - link_density(element)))
+}
+
+/// Decide whether a sibling of the top candidate belongs to the same article
+fn is_related(sibling: &ElementRef, scores: &HashMap<NodeId, f64>, threshold: f64) -> bool {
+    if final_score(sibling, scores).is_some_and(|s| s >= threshold) {
+        return true;
+    }
+    if sibling.value().name() != "p" {
+        return false;
+    }
+
+    let text = normalize_whitespace(&inline_text(*sibling));
+    let length = text.chars().count();
+    let density = link_density(sibling);
+    (length > 80 && density < 0.25) || (length > 0 && density == 0.0 && text.contains(". "))
+}
+
+fn link_density(element: &ElementRef) -> f64 {
+    let total = inline_text(*element).chars().count();
+    if total == 0 {
+        return 0.0;
+    }
+    let linked: usize = element
+        .descendent_elements()
+        .filter(|e| e.value().name() == "a")
+        .map(|a| inline_text(a).chars().count())
+        .sum();
+    linked as f64 / total as f64
+}
+
+fn has_content_ancestor(element: &ElementRef) -> bool {
+    element
+        .ancestors()
+        .filter_map(ElementRef::wrap)
+        .any(|a| matches!(a.value().name(), "article" | "main"))
+}
+
+fn class_and_id(element: &ElementRef) -> String {
+    let value = element.value();
+    format!(
+        "{} {}",
+        value.attr("class").unwrap_or_default(),
+        value.id().unwrap_or_default()
+    )
+    .to_lowercase()
+}
+
+/// Append the blocks of text found in `element` to `blocks`
+fn collect_element(element: ElementRef, blocks: &mut Vec<String>) {
+    match element.value().name() {
+        "pre" => push_block(blocks, inline_text(element).trim_matches('\n').to_string()),
+        "tr" => {
+            let cells = element
+                .child_elements()
+                .filter(|c| matches!(c.value().name(), "td" | "th"))
+                .map(|c| normalize_whitespace(&inline_text(c)))
+                .collect::<Vec<_>>();
+            push_block(blocks, cells.join(" | "));
+        }
+        _ => walk_children(element, blocks, collect_element, inline_text),
+    }
+}
+
+/// Walk the children of a block element, grouping inline runs into paragraphs.
+/// Boilerplate is skipped; block-level children are handed to `block`, and the other
+/// children are rendered by `inline` into the surrounding paragraph.
+pub(crate) fn walk_children<'a>(
+    element: ElementRef<'a>,
+    blocks: &mut Vec<String>,
+    block: impl Fn(ElementRef<'a>, &mut Vec<String>),
+    inline: impl Fn(ElementRef<'a>) -> String,
+) {
+    let mut paragraph = String::new();
+    for child in element.children() {
+        match child.value() {
+            Node::Text(text) => paragraph.push_str(text),
+            Node::Element(_) => {
+                let Some(child) = ElementRef::wrap(child) else {
+                    continue;
+                };
+                if is_boilerplate(&child) {
+                    continue;
+                }
+                if BLOCK_TAGS.contains(&child.value().name()) {
+                    push_block(blocks, normalize_whitespace(&paragraph));
+                    paragraph.clear();
+                    block(child, blocks);
+                } else {
+                    paragraph.push_str(&inline(child));
+                }
+            }
+            _ => {}
+        }
+    }
+    push_block(blocks, normalize_whitespace(&paragraph));
+}
+
+fn push_block(blocks: &mut Vec<String>, block: String) {
+    if !block.trim().is_empty() {
+        blocks.push(block);
+    }
+}
+
+/// Concatenate the text under `element`, skipping boilerplate subtrees
+fn inline_text(element: ElementRef) -> String {
+    walk_inline(element, inline_text)
+}
+
+/// Concatenate the text children of `element` and the children elements rendered
+/// by `inline`, skipping boilerplate
+pub(crate) fn walk_inline<'a>(
+    element: ElementRef<'a>,
+    inline: impl Fn(ElementRef<'a>) -> String,
+) -> String {
+    let mut text = String::new();
+    for child in element.children() {
+        match child.value() {
+            Node::Text(t) => text.push_str(t),
+            Node::Element(_) => {
+                if let Some(child) = ElementRef::wrap(child) {
+                    if !is_boilerplate(&child) {
+                        text.push_str(&inline(child));
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    text
+}
+
+pub(crate) fn normalize_whitespace(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NEWS_ARTICLE: &str = include_str!("../../tests/fixtures/html/news_article.html");
+    const DOCS_PAGE: &str = include_str!("../../tests/fixtures/html/docs_page.html");
+    const BLOG_POST: &str = include_str!("../../tests/fixtures/html/blog_post.html");
+    const WEBFORMS_PAGE: &str = include_str!("../../tests/fixtures/html/webforms_page.html");
+
+    fn extract_text(html: &str) -> String {
+        let document = Html::parse_document(html);
+        to_text(&extract_main_content(&document))
+    }
+
+    #[test]
+    fn test_news_article_keeps_story_and_drops_chrome() {
+        let text = extract_text(NEWS_ARTICLE);
+
+        assert!(text.contains("The city council approved the new transit plan on Tuesday"));
+        assert!(text.contains("Construction is expected to begin next spring"));
+        assert!(!text.contains("Accept all cookies"));
+        assert!(!text.contains("Subscribe to our newsletter"));
+        assert!(!text.contains("Most read"));
+        assert!(!text.contains("© 2025 Example News"));
+        assert!(!text.contains("trackPageView"));
+    }
+
+    #[test]
+    fn test_docs_page_keeps_lists_code_and_tables() {
+        let text = extract_text(DOCS_PAGE);
+
+        assert!(text.contains("Installation"));
+        assert!(text.contains("Download the archive for your platform"));
+        assert!(text.contains("cargo install cipherant"));
+        assert!(text.contains("--verbose | Print debug output"));
+        assert!(!text.contains("Getting started"));
+        assert!(!text.contains("Edit this page"));
+    }
+
+    #[test]
+    fn test_blog_post_drops_comments_and_site_header() {
+        let text = extract_text(BLOG_POST);
+
+        assert!(text.contains("After ten years of typing on laptop keyboards"));
+        assert!(text.contains("The best keyboard is the one you forget you are using."));
+        assert!(!text.contains("My Tiny Blog"));
+        assert!(!text.contains("Great post, thanks for sharing"));
+    }
+
+    #[test]
+    fn test_webforms_page_keeps_content_inside_form() {
+        let text = extract_text(WEBFORMS_PAGE);
+
+        assert!(text.contains("The city tested drinking water from all four treatment plants"));
+        assert!(text.contains("request a free kit from the utilities office"));
+        assert!(!text.contains("Council"));
+        assert!(!text.contains("All rights reserved"));
+        assert!(!text.contains("VIEWSTATE"));
+    }
+
+    #[test]
+    fn test_div_text_without_paragraphs_is_extracted() {
+        let html = r#"
+            <html><body>
+                <div class="content">
+                    <div>Plain text placed directly inside a div element, with no paragraph tags.</div>
+                </div>
+            </body></html>
+        "#;
+
+        let text = extract_text(html);
+
+        assert_eq!(
+            text,
+            "Plain text placed directly inside a div element, with no paragraph tags."
+        );
+    }
+
+    #[test]
+    fn test_pre_keeps_line_breaks() {
+        let html = "<html><body><pre>fn main() {\n    println!(\"hi\");\n}</pre></body></html>";
+
+        let text = extract_text(html);
+
+        assert_eq!(text, "fn main() {\n    println!(\"hi\");\n}");
+    }
+
+    #[test]
+    fn test_inline_elements_stay_in_one_block() {
+        let html =
+            "<html><body><p>Read <a href='/a'>the docs</a> and <em>enjoy</em>.</p></body></html>";
+
+        let text = extract_text(html);
+
+        assert_eq!(text, "Read the docs and enjoy.");
+    }
+
+    #[test]
+    fn test_is_boilerplate_by_tag_role_and_class() {
+        let html = r#"
+            <html><body>
+                <nav id="a"></nav>
+                <div id="b" role="navigation"></div>
+                <div id="c" class="cookie-banner"></div>
+                <div id="d" class="main-content sidebar-aware"></div>
+                <p id="e"></p>
+            </body></html>
+        "#;
+        let document = Html::parse_document(html);
+        let by_id = |id: &str| {
+            let selector = scraper::Selector::parse(&format!("#{}", id)).unwrap();
+            document.select(&selector).next().unwrap()
+        };
+
+        assert!(is_boilerplate(&by_id("a")));
+        assert!(is_boilerplate(&by_id("b")));
+        assert!(is_boilerplate(&by_id("c")));
+        assert!(!is_boilerplate(&by_id("d")));
+        assert!(!is_boilerplate(&by_id("e")));
+    }
+}
@@ -0,0 +1,370 @@
+use std::future::Future;
+use std::io;
+use std::time::{Duration, SystemTime};
+
+use anyhow::Result;
+use log::debug;
+use reqwest::header::{HeaderMap, RETRY_AFTER};
+use reqwest::StatusCode;
+
+use super::web::HttpResponse;
+
+/// How requests failing with a transient error (timeout, connection error, 408, 429,
+/// 502, 503, 504) are retried
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    /// Maximum number of attempts, including the first one (1 disables retries)
+    pub max_attempts: u32,
+    /// Delay before the first retry, doubled for each further retry
+    pub base_delay: Duration,
+    /// Upper bound on a backoff delay. A server asking (with `Retry-After`) to wait
+    /// longer than this is not retried.
+    pub max_delay: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            base_delay: Duration::from_millis(500),
+            max_delay: Duration::from_secs(30),
+        }
+    }
+}
+
+/// What the retry loop should do after an attempt
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) enum Verdict {
+    Done,
+    /// Try again, after the delay asked for by the server if any
+    Retry(Option<Duration>),
+}
+
+/// Outcome of an attempt that can tell whether it is worth retrying
+pub(crate) trait Retryable {
+    fn verdict(&self) -> Verdict;
+}
+
+impl RetryPolicy {
+    /// Run `attempt` until it succeeds, fails permanently or runs out of attempts,
+    /// and return the last outcome. `target` names what is requested, for logs.
+    pub(crate) async fn run<T, F, Fut>(&self, target: &str, mut attempt: F) -> T
+    where
+        T: Retryable,
+        F: FnMut() -> Fut,
+        Fut: Future<Output = T>,
+    {
+        let mut attempts = 1;
+        loop {
+            let outcome = attempt().await;
+            let Verdict::Retry(retry_after) = outcome.verdict() else {
+                return outcome;
+            };
+            if attempts >= self.max_attempts {
+                debug!("Giving up on {} after {} attempts", target, attempts);
+                return outcome;
+            }
+            let delay = match retry_after {
+                Some(delay) if delay > self.max_delay => {
+                    debug!("Not retrying {}: asked to wait {:?}", target, delay);
+                    return outcome;
+                }
+                Some(delay) => delay,
+                None => self.backoff(attempts),
+            };
+            debug!(
+                "Retrying {} in {:?} (attempt {} of {})",
+                target,
+                delay,
+                attempts + 1,
+                self.max_attempts
+            );
+            tokio::time::sleep(delay).await;
+            attempts += 1;
+        }
+    }
+
+    /// Exponential backoff with jitter: a random delay between half and all of
+    /// `base_delay * 2^(retry - 1)`, at most `max_delay`
+    fn backoff(&self, retry: u32) -> Duration {
+        let delay = self
+            .base_delay
+            .saturating_mul(2u32.saturating_pow(retry - 1))
+            .min(self.max_delay);
+        delay.mul_f64(0.5 + fastrand::f64() * 0.5)
+    }
+}
+
+impl Retryable for Result<HttpResponse> {
+    fn verdict(&self) -> Verdict {
+        match self {
+            Ok(response) => status_verdict(response.status, &response.headers),
+            Err(e) if is_transient_error(e) => Verdict::Retry(None),
+            Err(_) => Verdict::Done,
+        }
+    }
+}
+
+impl Retryable for reqwest::Result<reqwest::Response> {
+    fn verdict(&self) -> Verdict {
+        match self {
+            Ok(response) => status_verdict(response.status(), response.headers()),
+            Err(e) if is_transient_reqwest_error(e) => Verdict::Retry(None),
+            Err(_) => Verdict::Done,
+        }
+    }
+}
+
+/// Outcome of a request that isn't idempotent (e.g. a POST). It is only retried
+/// when the server can't have acted on it: on connection failures, 429 and 503.
+/// A timeout or a gateway error may come after the server processed the request.
+pub(crate) struct NotIdempotent(pub reqwest::Result<reqwest::Response>);
+
+impl Retryable for NotIdempotent {
+    fn verdict(&self) -> Verdict {
+        match &self.0 {
+            Ok(response) => match response.status().as_u16() {
+                429 | 503 => Verdict::Retry(retry_after(response.headers())),
+                _ => Verdict::Done,
+            },
+            Err(e) if e.is_connect() => Verdict::Retry(None),
+            Err(_) => Verdict::Done,
+        }
+    }
+}
+
+fn status_verdict(status: StatusCode, headers: &HeaderMap) -> Verdict {
+    match status.as_u16() {
+        408 | 429 | 502 | 503 | 504 => Verdict::Retry(retry_after(headers)),
+        _ => Verdict::Done,
+    }
+}
+
+/// Timeouts, connection failures and interrupted bodies
+fn is_transient_error(error: &anyhow::Error) -> bool {
+    error.chain().any(|cause| {
+        cause
+            .downcast_ref::<reqwest::Error>()
+            .is_some_and(is_transient_reqwest_error)
+            || cause
+                .downcast_ref::<io::Error>()
+                .is_some_and(|e| is_transient_io_error(e.kind()))
+    })
+}
+
+/// I/O errors of a network connection that failed along the way
+fn is_transient_io_error(kind: io::ErrorKind) -> bool {
+    matches!(
+        kind,
+        io::ErrorKind::ConnectionReset
+            | io::ErrorKind::ConnectionAborted
+            | io::ErrorKind::TimedOut
+            | io::ErrorKind::UnexpectedEof
+    )
+}
+
+fn is_transient_reqwest_error(error: &reqwest::Error) -> bool {
+    error.is_timeout() || error.is_connect() || error.is_body()
+}
+
+/// `Retry-After` as a number of seconds or an HTTP date
+pub(crate) fn retry_after(headers: &HeaderMap) -> Option<Duration> {
+    let value = headers.get(RETRY_AFTER)?.to_str().ok()?.trim();
+    if let Ok(seconds) = value.parse::<u64>() {
+        return Some(Duration::from_secs(seconds));
+    }
+    let date = httpdate::parse_http_date(value).ok()?;
+    Some(
+        date.duration_since(SystemTime::now())
+            .unwrap_or(Duration::ZERO),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::collectors::web::HttpClient;
+    use std::collections::VecDeque;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Mutex;
+    use tokio::time::Instant;
+
+    /// Mock client answering with a script of outcomes, one per request
+    struct ScriptedClient {
+        script: Mutex<VecDeque<Result<HttpResponse>>>,
+        requests: AtomicUsize,
+    }
+
+    impl ScriptedClient {
+        fn new(script: Vec<Result<HttpResponse>>) -> Self {
+            Self {
+                script: Mutex::new(script.into()),
+                requests: AtomicUsize::new(0),
+            }
+        }
+
+        fn requests(&self) -> usize {
+            self.requests.load(Ordering::SeqCst)
+        }
+    }
+
+    impl HttpClient for ScriptedClient {
+        async fn get(&self, _url: &str) -> Result<HttpResponse> {
+            self.requests.fetch_add(1, Ordering::SeqCst);
+            self.script
+                .lock()
+                .unwrap()
+                .pop_front()
+                .expect("more requests than scripted")
+        }
+    }
+
+    fn status(status: u16) -> Result<HttpResponse> {
+        Ok(HttpResponse::new(URL, status, "text/html", b""))
+    }
+
+    fn status_with_retry_after(status: u16, retry_after: &str) -> Result<HttpResponse> {
+        let mut response = HttpResponse::new(URL, status, "text/html", b"");
+        response
+            .headers
+            .insert(RETRY_AFTER, retry_after.parse().unwrap());
+        Ok(response)
+    }
+
+    fn timeout() -> Result<HttpResponse> {
+        Err(io::Error::new(io::ErrorKind::TimedOut, "timed out").into())
+    }
+
+    const URL: &str = "https://example.com/page";
+
+    async fn get(policy: &RetryPolicy, client: &ScriptedClient) -> Result<HttpResponse> {
+        policy.run(URL, || client.get(URL)).await
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_retries_transient_status_with_backoff() {
+        let client = ScriptedClient::new(vec![status(503), status(502), status(200)]);
+        let started = Instant::now();
+
+        let response = get(&RetryPolicy::default(), &client).await.unwrap();
+
+        assert_eq!(response.status, StatusCode::OK);
+        assert_eq!(client.requests(), 3);
+        // 250..=500ms, then 500..=1000ms
+        let elapsed = started.elapsed();
+        assert!(elapsed >= Duration::from_millis(750), "{:?}", elapsed);
+        assert!(elapsed <= Duration::from_millis(1500), "{:?}", elapsed);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_honours_retry_after_seconds() {
+        let client = ScriptedClient::new(vec![status_with_retry_after(429, "3"), status(200)]);
+        let started = Instant::now();
+
+        let response = get(&RetryPolicy::default(), &client).await.unwrap();
+
+        assert_eq!(response.status, StatusCode::OK);
+        assert_eq!(started.elapsed(), Duration::from_secs(3));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_gives_up_when_retry_after_exceeds_max_delay() {
+        let client = ScriptedClient::new(vec![status_with_retry_after(503, "3600")]);
+
+        let response = get(&RetryPolicy::default(), &client).await.unwrap();
+
+        assert_eq!(response.status, StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(client.requests(), 1);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_returns_last_outcome_after_max_attempts() {
+        let client = ScriptedClient::new(vec![timeout(), status(503), status(504)]);
+
+        let response = get(&RetryPolicy::default(), &client).await.unwrap();
+
+        assert_eq!(response.status, StatusCode::GATEWAY_TIMEOUT);
+        assert_eq!(client.requests(), 3);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_retries_timeouts() {
+        let client = ScriptedClient::new(vec![timeout(), status(200)]);
+
+        let response = get(&RetryPolicy::default(), &client).await.unwrap();
+
+        assert_eq!(response.status, StatusCode::OK);
+        assert_eq!(client.requests(), 2);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_does_not_retry_permanent_failures() {
+        let policy = RetryPolicy::default();
+
+        let not_found = ScriptedClient::new(vec![status(404)]);
+        assert_eq!(
+            get(&policy, &not_found).await.unwrap().status,
+            StatusCode::NOT_FOUND
+        );
+        assert_eq!(not_found.requests(), 1);
+
+        let invalid = ScriptedClient::new(vec![Err(anyhow::anyhow!("invalid URL"))]);
+        assert!(get(&policy, &invalid).await.is_err());
+        assert_eq!(invalid.requests(), 1);
+
+        let denied = ScriptedClient::new(vec![Err(io::Error::new(
+            io::ErrorKind::PermissionDenied,
+            "permission denied",
+        )
+        .into())]);
+        assert!(get(&policy, &denied).await.is_err());
+        assert_eq!(denied.requests(), 1);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_single_attempt_disables_retries() {
+        let policy = RetryPolicy {
+            max_attempts: 1,
+            ..RetryPolicy::default()
+        };
+        let client = ScriptedClient::new(vec![status(503)]);
+
+        get(&policy, &client).await.unwrap();
+
+        assert_eq!(client.requests(), 1);
+    }
+
+    #[test]
+    fn test_backoff_doubles_up_to_max_delay() {
+        let policy = RetryPolicy {
+            max_attempts: 10,
+            base_delay: Duration::from_secs(1),
+            max_delay: Duration::from_secs(5),
+        };
+
+        for (retry, full) in [(1, 1), (2, 2), (3, 4), (4, 5), (8, 5)] {
+            let delay = policy.backoff(retry);
+            let full = Duration::from_secs(full);
+            assert!(delay >= full / 2 && delay <= full, "{:?}", delay);
+        }
+    }
+
+    #[test]
+    fn test_retry_after_http_date() {
+        let mut headers = HeaderMap::new();
+        let in_a_minute = SystemTime::now() + Duration::from_secs(60);
+        headers.insert(
+            RETRY_AFTER,
+            httpdate::fmt_http_date(in_a_minute).parse().unwrap(),
+        );
+
+        let delay = retry_after(&headers).unwrap();
+        assert!(delay > Duration::from_secs(55) && delay <= Duration::from_secs(60));
+
+        headers.insert(
+            RETRY_AFTER,
+            "Wed, 21 Oct 2015 07:28:00 GMT".parse().unwrap(),
+        );
+        assert_eq!(retry_after(&headers), Some(Duration::ZERO));
+    }
+}
@@ -1,28 +1,91 @@
-use std::collections::HashMap;
+use std::num::NonZeroUsize;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
 
 use log::{debug, warn};
-use reqwest::Url;
+use lru::LruCache;
+use reqwest::{StatusCode, Url};
 use texting_robots::{get_robots_url, Robot};
-use tokio::sync::Mutex;
+use tokio::sync::OnceCell;
+use tokio::time::Instant;
 
-use super::web::{HttpClient, USER_AGENT};
+use super::web::{redirect_target, HttpClient, USER_AGENT};
 
-/// Cache for robots.txt per domain origin.
-/// Stores parsed `Robot` instances keyed by origin (e.g. "https://example.com").
-/// Uses `tokio::sync::Mutex` for interior mutability so callers only need `&self`.
+/// How long a fetched (or missing) robots.txt is trusted; RFC 9309 caps this at 24 hours
+const ROBOTS_TTL: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// How long a site whose robots.txt was unreachable stays fully disallowed before retrying
+const UNREACHABLE_TTL: Duration = Duration::from_secs(10 * 60);
+
+/// Maximum number of consecutive redirects followed for robots.txt (RFC 9309 asks for at least 5)
+const MAX_ROBOTS_REDIRECTS: usize = 5;
+
+/// Maximum robots.txt size that is parsed (RFC 9309 asks for at least 500 KiB)
+const MAX_ROBOTS_BYTES: usize = 500 * 1024;
+
+/// Rules of a site's robots.txt, as far as our User-Agent is concerned
+enum RobotsRules {
+    /// robots.txt was fetched and parsed
+    Parsed(Robot),
+    /// robots.txt is unavailable (4xx, too many redirects) or unparsable: no restrictions
+    AllowAll,
+    /// robots.txt is unreachable (5xx, network error): crawling is not allowed for now
+    DisallowAll,
+}
+
+impl RobotsRules {
+    fn allowed(&self, url: &str) -> bool {
+        match self {
+            RobotsRules::Parsed(robot) => robot.allowed(url),
+            RobotsRules::AllowAll => true,
+            RobotsRules::DisallowAll => false,
+        }
+    }
+
+    /// How long these rules may be cached
+    fn ttl(&self) -> Duration {
+        match self {
+            RobotsRules::DisallowAll => UNREACHABLE_TTL,
+            _ => ROBOTS_TTL,
+        }
+    }
+}
+
+struct CachedRules {
+    rules: RobotsRules,
+    expires_at: Instant,
+}
+
+/// Number of origins whose robots.txt rules are kept by default
+const DEFAULT_CAPACITY: usize = 1024;
+
+/// Cache for robots.txt per domain origin, following RFC 9309.
+/// Stores the rules keyed by origin (e.g. "https://example.com") until they expire,
+/// evicting the least recently used origins beyond its capacity.
+///
+/// Each origin's rules live in a `OnceCell`, so concurrent requests for one origin
+/// wait on a single robots.txt fetch. The map itself is behind a `std::sync::Mutex`
+/// that is only held for lookups, never across network I/O.
 pub(crate) struct RobotsCache {
-    cache: tokio::sync::Mutex<HashMap<String, Option<Robot>>>,
+    cache: Mutex<LruCache<String, Arc<OnceCell<CachedRules>>>>,
 }
 
 impl RobotsCache {
     pub(crate) fn new() -> Self {
+        Self::with_capacity(DEFAULT_CAPACITY)
+    }
+
+    /// Create a cache keeping the rules of at most `capacity` origins
+    pub(crate) fn with_capacity(capacity: usize) -> Self {
+        let capacity = NonZeroUsize::new(capacity).unwrap_or(NonZeroUsize::MIN);
         Self {
-            cache: Mutex::new(HashMap::new()),
+            cache: Mutex::new(LruCache::new(capacity)),
         }
     }
 
     /// Check if the given URL is allowed by the site's robots.txt.
-    /// Returns `true` (allow) on fetch/parse errors (graceful fallback).
+    /// Unavailable robots.txt (4xx) allows everything; unreachable robots.txt
+    /// (5xx or network error) disallows everything until retried.
     pub(crate) async fn is_allowed<C: HttpClient>(&self, client: &C, url: &str) -> bool {
         // http://exmaple.com/somethig/... -> http://exmaple.com
         let extracted_url = match extract_origin(url) {
@@ -30,16 +93,6 @@ impl RobotsCache {
             None => return true,
         };
 
-        // Check whether this URL has already been visited
-        {
-            let locked_cache = self.cache.lock().await;
-            match locked_cache.get(&extracted_url) {
-                Some(Some(r)) => return r.allowed(url),
-                Some(None) => return true,
-                None => {}
-            };
-        }
-
         let robots_url = match get_robots_url(&extracted_url) {
             Ok(u) => u,
             Err(e) => {
@@ -48,34 +101,107 @@ impl RobotsCache {
             }
         };
 
-        // Download robots.txt from URL
-        let robot_txt = match client.get(&robots_url).await {
+        let cell = self.cell(extracted_url);
+        let cached = cell
+            .get_or_init(|| async {
+                let rules = fetch_rules(client, &robots_url).await;
+                CachedRules {
+                    expires_at: Instant::now() + rules.ttl(),
+                    rules,
+                }
+            })
+            .await;
+
+        cached.rules.allowed(url)
+    }
+
+    /// `Crawl-delay` declared for our User-Agent by the cached robots.txt of the URL's origin.
+    /// Returns `None` if robots.txt hasn't been fetched yet or declares no delay.
+    pub(crate) fn crawl_delay(&self, url: &str) -> Option<Duration> {
+        let origin = extract_origin(url)?;
+        let cell = self.cache.lock().unwrap().peek(&origin)?.clone();
+        let RobotsRules::Parsed(robot) = &cell.get()?.rules else {
+            return None;
+        };
+        Duration::try_from_secs_f32(robot.delay?).ok()
+    }
+
+    /// The cell holding an origin's rules, replacing it if its rules have expired
+    fn cell(&self, origin: String) -> Arc<OnceCell<CachedRules>> {
+        let mut locked_cache = self.cache.lock().unwrap();
+        if let Some(cell) = locked_cache.get(&origin) {
+            // An empty cell is being filled by another request: wait for it
+            let expired = cell
+                .get()
+                .is_some_and(|cached| cached.expires_at <= Instant::now());
+            if !expired {
+                return cell.clone();
+            }
+        }
+        let cell = Arc::new(OnceCell::new());
+        locked_cache.put(origin, cell.clone());
+        cell
+    }
+}
+
+/// Download robots.txt, following up to `MAX_ROBOTS_REDIRECTS` redirects,
+/// and map the outcome to rules as RFC 9309 section 2.3 describes
+async fn fetch_rules<C: HttpClient>(client: &C, robots_url: &str) -> RobotsRules {
+    let mut url = robots_url.to_string();
+    for _ in 0..=MAX_ROBOTS_REDIRECTS {
+        let response = match client.get_no_redirect_limited(&url, MAX_ROBOTS_BYTES).await {
             Ok(r) => r,
             Err(e) => {
-                debug!("Failed to get robots.txt: {}", e);
-                let mut locked_cache = self.cache.lock().await;
-                locked_cache.insert(extracted_url, None);
-                return true;
+                debug!("Failed to get robots.txt from {}: {}", url, e);
+                return RobotsRules::DisallowAll;
             }
         };
 
-        // Build the Robot for our friendly User-Agent
-        let result: bool;
-        {
-            let mut locked_cache = self.cache.lock().await;
-            let robot = match Robot::new(USER_AGENT, robot_txt.as_bytes()) {
-                Ok(r) => r,
-                Err(e) => {
-                    warn!("robots.txt might be invalid: {}", e);
-                    locked_cache.insert(extracted_url, None);
-                    return true;
+        let status = response.status;
+        if status.is_redirection() {
+            match redirect_target(&url, &response) {
+                Some(target) => {
+                    url = target;
+                    continue;
                 }
-            };
-            result = robot.allowed(url);
-            locked_cache.insert(extracted_url, Some(robot));
+                None => return RobotsRules::AllowAll,
+            }
+        }
+        // Rate limiting means the server is struggling, like a 5xx
+        if status.is_server_error() || status == StatusCode::TOO_MANY_REQUESTS {
+            debug!("robots.txt at {} is unreachable (HTTP {})", url, status);
+            return RobotsRules::DisallowAll;
+        }
+        if !status.is_success() {
+            debug!("No robots.txt at {} (HTTP {})", url, status);
+            return RobotsRules::AllowAll;
         }
 
-        result
+        return parse_rules(&response.body, response.truncated);
+    }
+
+    debug!("Too many redirects for {}", robots_url);
+    RobotsRules::AllowAll
+}
+
+/// Build the `Robot` for our friendly User-Agent from the first `MAX_ROBOTS_BYTES` of
+/// the file, which may already have been cut there by the client
+fn parse_rules(body: &[u8], truncated: bool) -> RobotsRules {
+    let body = if truncated || body.len() > MAX_ROBOTS_BYTES {
+        // Drop the line cut in half by the limit
+        let head = &body[..MAX_ROBOTS_BYTES.min(body.len())];
+        let end = head.iter().rposition(|&b| b == b'\n').unwrap_or(0);
+        &head[..end]
+    } else {
+        body
+    };
+
+    match Robot::new(USER_AGENT, body) {
+        Ok(robot) => RobotsRules::Parsed(robot),
+        Err(e) => {
+            warn!("robots.txt might be invalid: {}", e);
+            RobotsRules::AllowAll
+        }
     }
 }
 
@@ -102,27 +228,64 @@ pub(crate) fn extract_origin(url: &str) -> Option<String> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::collectors::web::HttpResponse;
     use anyhow::Result;
+    use reqwest::header::LOCATION;
+    use std::collections::HashMap;
+    use std::sync::atomic::{AtomicUsize, Ordering};
 
+    /// Mock client; unknown URLs fail like an unreachable server
     struct MockHttpClient {
-        responses: HashMap<String, String>,
+        responses: HashMap<String, HttpResponse>,
+        latencies: HashMap<String, Duration>,
+        requests: AtomicUsize,
     }
 
     impl MockHttpClient {
         fn new() -> Self {
             Self {
                 responses: HashMap::new(),
+                latencies: HashMap::new(),
+                requests: AtomicUsize::new(0),
             }
         }
 
-        fn with_response(mut self, url: &str, body: &str) -> Self {
-            self.responses.insert(url.to_string(), body.to_string());
+        /// Make requests to `url` take `latency` to answer
+        fn with_latency(mut self, url: &str, latency: Duration) -> Self {
+            self.latencies.insert(url.to_string(), latency);
+            self
+        }
+
+        fn with_response(self, url: &str, body: &str) -> Self {
+            self.with_http_response(HttpResponse::new(url, 200, "text/plain", body.as_bytes()))
+        }
+
+        fn with_status(self, url: &str, status: u16) -> Self {
+            self.with_http_response(HttpResponse::new(url, status, "text/html", b"error page"))
+        }
+
+        fn with_redirect(self, url: &str, location: &str) -> Self {
+            let mut response = HttpResponse::new(url, 301, "text/html", b"");
+            response.headers.insert(LOCATION, location.parse().unwrap());
+            self.with_http_response(response)
+        }
+
+        fn with_http_response(mut self, response: HttpResponse) -> Self {
+            self.responses.insert(response.url.clone(), response);
             self
         }
+
+        fn request_count(&self) -> usize {
+            self.requests.load(Ordering::SeqCst)
+        }
     }
 
     impl HttpClient for MockHttpClient {
-        async fn get(&self, url: &str) -> Result<String> {
+        async fn get(&self, url: &str) -> Result<HttpResponse> {
+            self.requests.fetch_add(1, Ordering::SeqCst);
+            if let Some(latency) = self.latencies.get(url) {
+                tokio::time::sleep(*latency).await;
+            }
             self.responses
                 .get(url)
                 .cloned()
@@ -180,14 +343,113 @@ mod tests {
     }
 
     #[tokio::test]
-    async fn test_allowed_when_robots_txt_fetch_fails() {
-        // No mock response for robots.txt => fetch fails => graceful fallback to allow
+    async fn test_disallowed_when_robots_txt_unreachable() {
+        // No mock response for robots.txt => network error => disallow everything
         let client = MockHttpClient::new();
         let cache = RobotsCache::new();
 
+        assert!(!cache.is_allowed(&client, "https://example.com/page").await);
+    }
+
+    #[tokio::test]
+    async fn test_allowed_when_robots_txt_not_found() {
+        let client = MockHttpClient::new().with_status("https://example.com/robots.txt", 404);
+        let cache = RobotsCache::new();
+
         assert!(cache.is_allowed(&client, "https://example.com/page").await);
     }
 
+    #[tokio::test]
+    async fn test_disallowed_when_robots_txt_server_error() {
+        let client = MockHttpClient::new().with_status("https://example.com/robots.txt", 503);
+        let cache = RobotsCache::new();
+
+        assert!(!cache.is_allowed(&client, "https://example.com/page").await);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_unreachable_robots_txt_is_retried_sooner() {
+        let client = MockHttpClient::new().with_status("https://example.com/robots.txt", 500);
+        let cache = RobotsCache::new();
+
+        cache.is_allowed(&client, "https://example.com/a").await;
+        cache.is_allowed(&client, "https://example.com/b").await;
+        let before_retry = client.request_count();
+        tokio::time::advance(UNREACHABLE_TTL).await;
+        cache.is_allowed(&client, "https://example.com/c").await;
+
+        assert_eq!(before_retry, 1);
+        assert_eq!(client.request_count(), 2);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_cached_robots_txt_expires_after_ttl() {
+        let client = MockHttpClient::new()
+            .with_response("https://example.com/robots.txt", "User-agent: *\nAllow: /");
+        let cache = RobotsCache::new();
+
+        cache.is_allowed(&client, "https://example.com/a").await;
+        tokio::time::advance(ROBOTS_TTL - Duration::from_secs(1)).await;
+        cache.is_allowed(&client, "https://example.com/b").await;
+        let before_expiry = client.request_count();
+        tokio::time::advance(Duration::from_secs(1)).await;
+        cache.is_allowed(&client, "https://example.com/c").await;
+
+        assert_eq!(before_expiry, 1);
+        assert_eq!(client.request_count(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_follows_robots_txt_redirects_up_to_limit() {
+        let mut client = MockHttpClient::new().with_response(
+            "https://example.com/r5",
+            "User-agent: *\nDisallow: /private",
+        );
+        for i in 0..5 {
+            let from = if i == 0 {
+                "https://example.com/robots.txt".to_string()
+            } else {
+                format!("https://example.com/r{}", i)
+            };
+            client = client.with_redirect(&from, &format!("/r{}", i + 1));
+        }
+        let cache = RobotsCache::new();
+
+        assert!(
+            !cache
+                .is_allowed(&client, "https://example.com/private/page")
+                .await
+        );
+    }
+
+    #[tokio::test]
+    async fn test_too_many_robots_txt_redirects_allow_everything() {
+        let client = MockHttpClient::new()
+            .with_redirect("https://example.com/robots.txt", "/loop")
+            .with_redirect("https://example.com/loop", "/loop");
+        let cache = RobotsCache::new();
+
+        assert!(
+            cache
+                .is_allowed(&client, "https://example.com/private/page")
+                .await
+        );
+        assert_eq!(client.request_count(), MAX_ROBOTS_REDIRECTS + 1);
+    }
+
+    #[tokio::test]
+    async fn test_rules_beyond_size_limit_are_ignored() {
+        let mut robots_txt = "User-agent: *\nDisallow: /early\n".to_string();
+        robots_txt.push_str(&"# padding\n".repeat(MAX_ROBOTS_BYTES / 10));
+        robots_txt.push_str("Disallow: /late\n");
+        let client =
+            MockHttpClient::new().with_response("https://example.com/robots.txt", &robots_txt);
+        let cache = RobotsCache::new();
+
+        assert!(!cache.is_allowed(&client, "https://example.com/early").await);
+        assert!(cache.is_allowed(&client, "https://example.com/late").await);
+    }
+
     #[tokio::test]
     async fn test_cache_reuses_robots_txt_for_same_origin() {
         let client = MockHttpClient::new().with_response(
@@ -205,7 +467,81 @@ mod tests {
                 .await
         );
         // Only one entry in cache
-        let locked_cache = cache.cache.lock().await;
+        let locked_cache = cache.cache.lock().unwrap();
         assert_eq!(locked_cache.len(), 1);
     }
+
+    #[tokio::test]
+    async fn test_crawl_delay_from_cached_robots_txt() {
+        let client = MockHttpClient::new().with_response(
+            "https://example.com/robots.txt",
+            "User-agent: *\nCrawl-delay: 1.5\nDisallow:",
+        );
+        let cache = RobotsCache::new();
+
+        let before_fetch = cache.crawl_delay("https://example.com/page");
+        cache.is_allowed(&client, "https://example.com/page").await;
+        let after_fetch = cache.crawl_delay("https://example.com/other");
+
+        assert_eq!(before_fetch, None);
+        assert_eq!(after_fetch, Some(Duration::from_millis(1500)));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_concurrent_requests_share_one_robots_txt_fetch() {
+        let client = MockHttpClient::new()
+            .with_response("https://example.com/robots.txt", "User-agent: *\nAllow: /")
+            .with_latency("https://example.com/robots.txt", Duration::from_secs(1));
+        let cache = RobotsCache::new();
+        let urls: Vec<String> = (0..10)
+            .map(|i| format!("https://example.com/{}", i))
+            .collect();
+
+        let results =
+            futures::future::join_all(urls.iter().map(|url| cache.is_allowed(&client, url))).await;
+
+        assert!(results.into_iter().all(|allowed| allowed));
+        assert_eq!(client.request_count(), 1);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_slow_origin_does_not_block_other_origins() {
+        let client = MockHttpClient::new()
+            .with_status("https://slow.example/robots.txt", 404)
+            .with_latency("https://slow.example/robots.txt", Duration::from_secs(60))
+            .with_status("https://fast.example/robots.txt", 404);
+        let cache = RobotsCache::new();
+
+        let slow = cache.is_allowed(&client, "https://slow.example/page");
+        let fast = tokio::time::timeout(
+            Duration::from_secs(1),
+            cache.is_allowed(&client, "https://fast.example/page"),
+        );
+        let (_, fast) = tokio::join!(slow, fast);
+
+        assert_eq!(fast, Ok(true));
+    }
+
+    #[tokio::test]
+    async fn test_least_recently_used_origin_is_evicted() {
+        let client = MockHttpClient::new()
+            .with_status("https://a.example/robots.txt", 404)
+            .with_status("https://b.example/robots.txt", 404)
+            .with_status("https://c.example/robots.txt", 404);
+        let cache = RobotsCache::with_capacity(2);
+
+        cache.is_allowed(&client, "https://a.example/").await;
+        cache.is_allowed(&client, "https://b.example/").await;
+        cache.is_allowed(&client, "https://a.example/again").await;
+        cache.is_allowed(&client, "https://c.example/").await;
+        let before_refetch = client.request_count();
+        cache
+            .is_allowed(&client, "https://a.example/still-cached")
+            .await;
+        cache.is_allowed(&client, "https://b.example/evicted").await;
+
+        assert_eq!(before_refetch, 3);
+        assert_eq!(client.request_count(), 4);
+        assert_eq!(cache.cache.lock().unwrap().len(), 2);
+    }
 }
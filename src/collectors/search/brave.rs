@@ -0,0 +1,219 @@
+use futures::future::BoxFuture;
+use scraper::Html;
+use serde::Deserialize;
+
+use super::{
+    SearchError, SearchHttp, SearchOptions, SearchProvider, SearchResult, TimeRange, Topic,
+};
+
+const ENDPOINT: &str = "https://api.search.brave.com/res/v1/web/search";
+
+/// Brave returns at most 20 results per request
+const MAX_COUNT: usize = 20;
+
+/// Search through the Brave Search API
+pub struct Brave {
+    http: SearchHttp,
+    api_key: Option<String>,
+    endpoint: String,
+}
+
+#[derive(Deserialize)]
+struct BraveResponse {
+    web: Option<BraveSection>,
+    news: Option<BraveSection>,
+}
+
+#[derive(Deserialize)]
+struct BraveSection {
+    #[serde(default)]
+    results: Vec<BraveResult>,
+}
+
+#[derive(Deserialize)]
+struct BraveResult {
+    title: String,
+    url: String,
+    #[serde(default)]
+    description: String,
+}
+
+impl Brave {
+    pub(crate) fn new(http: SearchHttp, api_key: Option<String>) -> Self {
+        Self {
+            http,
+            api_key,
+            endpoint: ENDPOINT.to_string(),
+        }
+    }
+
+    #[cfg(test)]
+    fn with_endpoint(mut self, endpoint: String) -> Self {
+        self.endpoint = endpoint;
+        self
+    }
+
+    async fn search(
+        &self,
+        query: &str,
+        options: &SearchOptions,
+    ) -> Result<Vec<SearchResult>, SearchError> {
+        let api_key = self
+            .api_key
+            .as_deref()
+            .ok_or(SearchError::ApiKeyNotFound("BRAVE_API_KEY"))?;
+        let query = options.query_with_sites(query);
+        let count = options.max_results.clamp(1, MAX_COUNT).to_string();
+        let mut params = vec![("q", query.as_str()), ("count", count.as_str())];
+        if let Some(time_range) = options.time_range {
+            params.push(("freshness", freshness(time_range)));
+        }
+        if options.topic == Topic::News {
+            params.push(("result_filter", "news"));
+        }
+        let response = self
+            .http
+            .send(&self.endpoint, |client| {
+                client
+                    .get(&self.endpoint)
+                    .query(&params)
+                    .header("Accept", "application/json")
+                    .header("X-Subscription-Token", api_key)
+            })
+            .await?;
+        let response: BraveResponse = SearchHttp::json(response).await?;
+
+        let section = match options.topic {
+            Topic::General => response.web,
+            Topic::News => response.news,
+        };
+        Ok(section
+            .map(|section| section.results)
+            .unwrap_or_default()
+            .into_iter()
+            .map(|result| SearchResult {
+                title: strip_tags(&result.title),
+                url: result.url,
+                snippet: strip_tags(&result.description),
+            })
+            .collect())
+    }
+}
+
+fn freshness(time_range: TimeRange) -> &'static str {
+    match time_range {
+        TimeRange::Day => "pd",
+        TimeRange::Week => "pw",
+        TimeRange::Month => "pm",
+        TimeRange::Year => "py",
+    }
+}
+
+/// Brave highlights matches with `<strong>` and escapes entities
+fn strip_tags(html: &str) -> String {
+    Html::parse_fragment(html).root_element().text().collect()
+}
+
+impl SearchProvider for Brave {
+    fn name(&self) -> &'static str {
+        "brave"
+    }
+
+    fn search<'a>(
+        &'a self,
+        query: &'a str,
+        options: &'a SearchOptions,
+    ) -> BoxFuture<'a, Result<Vec<SearchResult>, SearchError>> {
+        Box::pin(Brave::search(self, query, options))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::collectors::retry::RetryPolicy;
+    use crate::collectors::web::{HttpConfig, ReqwestClient};
+    use wiremock::matchers::{header, method, path, query_param, query_param_is_missing};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    fn brave(server: &MockServer) -> Brave {
+        let client = ReqwestClient::new(&HttpConfig::default()).unwrap();
+        let http = SearchHttp::new(client, RetryPolicy::default());
+        Brave::new(http, Some("brave-test".to_string()))
+            .with_endpoint(format!("{}/res/v1/web/search", server.uri()))
+    }
+
+    #[tokio::test]
+    async fn test_search_parses_recorded_response() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/res/v1/web/search"))
+            .and(query_param("q", "rust async runtime"))
+            .and(query_param("count", "5"))
+            .and(query_param_is_missing("freshness"))
+            .and(header("X-Subscription-Token", "brave-test"))
+            .respond_with(ResponseTemplate::new(200).set_body_raw(
+                include_str!("../../../tests/fixtures/search/brave.json"),
+                "application/json",
+            ))
+            .mount(&server)
+            .await;
+
+        let results = brave(&server)
+            .search("rust async runtime", &SearchOptions::default())
+            .await
+            .unwrap();
+
+        assert_eq!(
+            results,
+            vec![
+                SearchResult {
+                    title: "Tokio - An asynchronous Rust runtime".to_string(),
+                    url: "https://tokio.rs/".to_string(),
+                    snippet: "Tokio is an event-driven, non-blocking I/O platform for writing asynchronous applications with the Rust programming language.".to_string(),
+                },
+                SearchResult {
+                    title: "smol - Rust".to_string(),
+                    url: "https://docs.rs/smol".to_string(),
+                    snippet: "A small and fast async runtime & executor.".to_string(),
+                },
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_search_maps_options_to_parameters() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/res/v1/web/search"))
+            .and(query_param("q", "tokio release (site:tokio.rs OR site:github.com)"))
+            .and(query_param("count", "20"))
+            .and(query_param("freshness", "pw"))
+            .and(query_param("result_filter", "news"))
+            .respond_with(ResponseTemplate::new(200).set_body_raw(
+                r#"{"type": "search", "news": {"type": "news", "results": [
+                    {"title": "Tokio 1.40 released", "url": "https://tokio.rs/blog/2024-09-tokio-1-40", "description": "Announcing <strong>Tokio</strong> 1.40"}
+                ]}}"#,
+                "application/json",
+            ))
+            .expect(1)
+            .mount(&server)
+            .await;
+        let options = SearchOptions {
+            max_results: 20,
+            time_range: Some(TimeRange::Week),
+            include_domains: vec!["tokio.rs".to_string(), "github.com".to_string()],
+            topic: Topic::News,
+            ..SearchOptions::default()
+        };
+
+        let results = brave(&server)
+            .search("tokio release", &options)
+            .await
+            .unwrap();
+
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].title, "Tokio 1.40 released");
+        assert_eq!(results[0].snippet, "Announcing Tokio 1.40");
+    }
+}
@@ -0,0 +1,183 @@
+use anyhow::anyhow;
+use futures::future::BoxFuture;
+use reqwest::Url;
+use scraper::{ElementRef, Html, Selector};
+
+use super::{SearchError, SearchHttp, SearchOptions, SearchProvider, SearchResult, TimeRange};
+use crate::collectors::encoding::decode_html;
+
+const ENDPOINT: &str = "https://html.duckduckgo.com/html/";
+
+/// Search by reading DuckDuckGo's HTML results page (no API key needed)
+pub struct DuckDuckGo {
+    http: SearchHttp,
+    endpoint: String,
+}
+
+impl DuckDuckGo {
+    pub(crate) fn new(http: SearchHttp) -> Self {
+        Self {
+            http,
+            endpoint: ENDPOINT.to_string(),
+        }
+    }
+
+    #[cfg(test)]
+    fn with_endpoint(mut self, endpoint: String) -> Self {
+        self.endpoint = endpoint;
+        self
+    }
+
+    async fn search(
+        &self,
+        query: &str,
+        options: &SearchOptions,
+    ) -> Result<Vec<SearchResult>, SearchError> {
+        let query = options.query_with_sites(query);
+        let mut params = vec![("q", query.as_str())];
+        if let Some(time_range) = options.time_range {
+            params.push(("df", date_filter(time_range)));
+        }
+        let response = self
+            .http
+            .send(&self.endpoint, |client| {
+                client.get(&self.endpoint).query(&params)
+            })
+            .await?;
+        let response = SearchHttp::read(response).await?;
+        let html = decode_html(&response.body, response.charset());
+
+        let results = parse_results(&html);
+        if results.is_empty() && html.contains("anomaly-modal") {
+            return Err(anyhow!("DuckDuckGo refused the query (bot check)").into());
+        }
+        Ok(results.into_iter().take(options.max_results).collect())
+    }
+}
+
+fn date_filter(time_range: TimeRange) -> &'static str {
+    match time_range {
+        TimeRange::Day => "d",
+        TimeRange::Week => "w",
+        TimeRange::Month => "m",
+        TimeRange::Year => "y",
+    }
+}
+
+/// Organic results of a results page, leaving out ads
+fn parse_results(html: &str) -> Vec<SearchResult> {
+    let document = Html::parse_document(html);
+    let result_selector = Selector::parse(".result:not(.result--ad)").unwrap();
+    let link_selector = Selector::parse("a.result__a").unwrap();
+    let snippet_selector = Selector::parse(".result__snippet").unwrap();
+
+    document
+        .select(&result_selector)
+        .filter_map(|result| {
+            let link = result.select(&link_selector).next()?;
+            let url = target_url(link.attr("href")?)?;
+            Some(SearchResult {
+                title: text(link),
+                url,
+                snippet: result
+                    .select(&snippet_selector)
+                    .next()
+                    .map(text)
+                    .unwrap_or_default(),
+            })
+        })
+        .collect()
+}
+
+/// Result links go through a redirect (`//duckduckgo.com/l/?uddg=<target>`)
+fn target_url(href: &str) -> Option<String> {
+    let url = Url::parse("https://duckduckgo.com/")
+        .ok()?
+        .join(href)
+        .ok()?;
+    if url.path() == "/l/" {
+        return url
+            .query_pairs()
+            .find(|(name, _)| name == "uddg")
+            .map(|(_, target)| target.into_owned());
+    }
+    Some(url.to_string())
+}
+
+fn text(element: ElementRef) -> String {
+    element
+        .text()
+        .collect::<String>()
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+impl SearchProvider for DuckDuckGo {
+    fn name(&self) -> &'static str {
+        "duckduckgo"
+    }
+
+    fn search<'a>(
+        &'a self,
+        query: &'a str,
+        options: &'a SearchOptions,
+    ) -> BoxFuture<'a, Result<Vec<SearchResult>, SearchError>> {
+        Box::pin(DuckDuckGo::search(self, query, options))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::collectors::retry::RetryPolicy;
+    use crate::collectors::web::{HttpConfig, ReqwestClient};
+    use wiremock::matchers::{method, path, query_param};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    const FIXTURE: &str = include_str!("../../../tests/fixtures/search/duckduckgo.html");
+
+    #[test]
+    fn test_parse_results_skips_ads_and_unwraps_redirects() {
+        let results = parse_results(FIXTURE);
+
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].title, "Tokio - An asynchronous Rust runtime");
+        assert_eq!(results[0].url, "https://tokio.rs/");
+        assert_eq!(
+            results[0].snippet,
+            "Tokio is an event-driven, non-blocking I/O platform for writing asynchronous applications with the Rust programming language."
+        );
+        assert_eq!(results[1].url, "https://github.com/smol-rs/smol");
+    }
+
+    #[tokio::test]
+    async fn test_search_reads_results_page() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/html/"))
+            .and(query_param("q", "rust async runtime -site:reddit.com"))
+            .and(query_param("df", "y"))
+            .respond_with(ResponseTemplate::new(200).set_body_raw(FIXTURE, "text/html"))
+            .mount(&server)
+            .await;
+        let client = ReqwestClient::new(&HttpConfig::default()).unwrap();
+        let duckduckgo = DuckDuckGo::new(SearchHttp::new(client, RetryPolicy::default()))
+            .with_endpoint(format!("{}/html/", server.uri()));
+
+        let options = SearchOptions {
+            max_results: 1,
+            time_range: Some(TimeRange::Year),
+            exclude_domains: vec!["reddit.com".to_string()],
+            ..SearchOptions::default()
+        };
+
+        let results = duckduckgo
+            .search("rust async runtime", &options)
+            .await
+            .unwrap();
+
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].url, "https://tokio.rs/");
+    }
+}
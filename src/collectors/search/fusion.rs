@@ -0,0 +1,199 @@
+use std::collections::HashMap;
+
+use reqwest::Url;
+use serde::Serialize;
+
+use super::SearchResult;
+
+/// Damping constant of reciprocal rank fusion; 60 is the value from the original paper
+const RRF_K: f64 = 60.0;
+
+/// Query parameters that only track where a visitor came from
+const TRACKING_PARAMS: &[&str] = &[
+    "fbclid", "gclid", "dclid", "msclkid", "yclid", "mc_cid", "mc_eid", "igshid", "ref_src",
+    "_hsenc", "_hsmi",
+];
+
+/// A search result merged from the results of several queries
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct FusedResult {
+    #[serde(flatten)]
+    pub result: SearchResult,
+    /// Queries whose results included this page
+    pub queries: Vec<String>,
+    /// Reciprocal rank fusion score; higher is better
+    pub score: f64,
+}
+
+/// Key identifying the same page behind different URLs: without fragment, tracking
+/// parameters or trailing slash. URLs that don't parse are only trimmed.
+pub fn normalize_url(url: &str) -> String {
+    let Ok(mut parsed) = Url::parse(url.trim()) else {
+        return url.trim().to_string();
+    };
+    parsed.set_fragment(None);
+    let params: Vec<(String, String)> = parsed
+        .query_pairs()
+        .filter(|(name, _)| !is_tracking_param(name))
+        .map(|(name, value)| (name.into_owned(), value.into_owned()))
+        .collect();
+    if params.is_empty() {
+        parsed.set_query(None);
+    } else {
+        parsed.query_pairs_mut().clear().extend_pairs(params);
+    }
+    let mut normalized = parsed.to_string();
+    if parsed.query().is_none() {
+        while normalized.ends_with('/') {
+            normalized.pop();
+        }
+    } else if let Some(index) = normalized.find("/?") {
+        normalized.remove(index);
+    }
+    normalized
+}
+
+fn is_tracking_param(name: &str) -> bool {
+    let name = name.to_ascii_lowercase();
+    name.starts_with("utm_") || TRACKING_PARAMS.contains(&name.as_str())
+}
+
+/// Merge the ranked results of several queries with reciprocal rank fusion: a page
+/// scores `1 / (60 + rank)` for each query that found it. Pages found by several
+/// queries under different URLs are merged by [`normalize_url`]; the title, URL
+/// and snippet come from the best ranked occurrence.
+pub fn fuse(ranked: &[(String, Vec<SearchResult>)]) -> Vec<FusedResult> {
+    let mut fused: Vec<FusedResult> = Vec::new();
+    let mut index_by_key: HashMap<String, usize> = HashMap::new();
+    // Best rank of each page, to keep the representative of the best occurrence
+    let mut best_ranks: Vec<usize> = Vec::new();
+
+    for (query, results) in ranked {
+        let mut seen = Vec::new();
+        for (rank, result) in results.iter().enumerate() {
+            let key = normalize_url(&result.url);
+            // A page listed twice by one query only counts once, at its best rank
+            if seen.contains(&key) {
+                continue;
+            }
+            let score = 1.0 / (RRF_K + (rank + 1) as f64);
+            match index_by_key.get(&key) {
+                Some(&index) => {
+                    let entry = &mut fused[index];
+                    entry.score += score;
+                    entry.queries.push(query.clone());
+                    if rank < best_ranks[index] {
+                        entry.result = result.clone();
+                        best_ranks[index] = rank;
+                    }
+                }
+                None => {
+                    index_by_key.insert(key.clone(), fused.len());
+                    best_ranks.push(rank);
+                    fused.push(FusedResult {
+                        result: result.clone(),
+                        queries: vec![query.clone()],
+                        score,
+                    });
+                }
+            }
+            seen.push(key);
+        }
+    }
+
+    // Stable sort: ties keep the order in which the pages were first found
+    fused.sort_by(|a, b| b.score.total_cmp(&a.score));
+    fused
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn result(url: &str, title: &str) -> SearchResult {
+        SearchResult {
+            title: title.to_string(),
+            url: url.to_string(),
+            snippet: String::new(),
+        }
+    }
+
+    #[test]
+    fn test_normalize_url() {
+        assert_eq!(normalize_url("https://tokio.rs/"), "https://tokio.rs");
+        assert_eq!(
+            normalize_url("https://Tokio.rs/blog/#top"),
+            "https://tokio.rs/blog"
+        );
+        assert_eq!(
+            normalize_url("https://tokio.rs/blog/?utm_source=x&utm_medium=y&fbclid=z"),
+            "https://tokio.rs/blog"
+        );
+        assert_eq!(
+            normalize_url("https://docs.rs/tokio/?search=spawn&utm_campaign=x"),
+            "https://docs.rs/tokio?search=spawn"
+        );
+        assert_eq!(normalize_url(" not a url "), "not a url");
+    }
+
+    #[test]
+    fn test_fuse_merges_duplicates_across_queries() {
+        let ranked = vec![
+            (
+                "tokio runtime".to_string(),
+                vec![
+                    result("https://tokio.rs/", "Tokio"),
+                    result("https://docs.rs/tokio", "tokio - Rust"),
+                ],
+            ),
+            (
+                "rust async runtime".to_string(),
+                vec![
+                    result("https://async.rs/", "async-std"),
+                    result("https://tokio.rs/?utm_source=search#intro", "Tokio runtime"),
+                ],
+            ),
+        ];
+
+        let fused = fuse(&ranked);
+
+        assert_eq!(fused.len(), 3);
+        assert_eq!(fused[0].result.url, "https://tokio.rs/");
+        assert_eq!(fused[0].result.title, "Tokio");
+        assert_eq!(
+            fused[0].queries,
+            vec!["tokio runtime", "rust async runtime"]
+        );
+        assert!((fused[0].score - (1.0 / 61.0 + 1.0 / 62.0)).abs() < 1e-12);
+        assert_eq!(fused[1].result.url, "https://async.rs/");
+        assert_eq!(fused[1].queries, vec!["rust async runtime"]);
+        assert_eq!(fused[2].result.url, "https://docs.rs/tokio");
+    }
+
+    #[test]
+    fn test_fuse_keeps_best_ranked_occurrence() {
+        let ranked = vec![
+            (
+                "a".to_string(),
+                vec![
+                    result("https://example.com/1", "One"),
+                    result("https://example.com/2", "Two (a)"),
+                ],
+            ),
+            (
+                "b".to_string(),
+                vec![
+                    result("https://example.com/2/", "Two (b)"),
+                    result("https://example.com/2", "Two again"),
+                ],
+            ),
+        ];
+
+        let fused = fuse(&ranked);
+
+        assert_eq!(fused.len(), 2);
+        assert_eq!(fused[0].result.title, "Two (b)");
+        assert_eq!(fused[0].queries, vec!["a", "b"]);
+        assert!((fused[0].score - (1.0 / 62.0 + 1.0 / 61.0)).abs() < 1e-12);
+    }
+}
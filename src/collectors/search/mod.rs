@@ -0,0 +1,397 @@
+mod brave;
+mod duckduckgo;
+mod fusion;
+mod searxng;
+mod tavily;
+
+use std::env;
+use std::sync::Arc;
+use std::time::Duration;
+
+use futures::future::BoxFuture;
+use reqwest::StatusCode;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+use super::retry::{self, NotIdempotent, RetryPolicy};
+use super::web::{read_response, FetchError, HttpResponse, ReqwestClient};
+
+pub use brave::Brave;
+pub use duckduckgo::DuckDuckGo;
+pub use fusion::{fuse, normalize_url, FusedResult};
+pub use searxng::Searxng;
+pub use tavily::Tavily;
+
+/// A single search result
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct SearchResult {
+    pub title: String,
+    pub url: String,
+    pub snippet: String,
+}
+
+/// Largest number of results a search may ask for
+pub const MAX_RESULTS: usize = 20;
+
+/// Largest search response body read; real result pages are far smaller
+const MAX_RESPONSE_BYTES: usize = 2 * 1024 * 1024;
+
+/// How far back results may go
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TimeRange {
+    Day,
+    Week,
+    Month,
+    Year,
+}
+
+impl TimeRange {
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Day => "day",
+            Self::Week => "week",
+            Self::Month => "month",
+            Self::Year => "year",
+        }
+    }
+}
+
+/// Kind of results wanted
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Topic {
+    #[default]
+    General,
+    News,
+}
+
+/// How thoroughly the provider searches; `advanced` is slower (and may cost more)
+/// but finds more relevant snippets
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SearchDepth {
+    #[default]
+    Basic,
+    Advanced,
+}
+
+/// What to search for besides the query. Backends without an equivalent for an
+/// option (e.g. `search_depth` outside Tavily) ignore it.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct SearchOptions {
+    /// Maximum number of results, 1 to [`MAX_RESULTS`]
+    pub max_results: usize,
+    pub time_range: Option<TimeRange>,
+    /// Only return results from these sites
+    pub include_domains: Vec<String>,
+    /// Never return results from these sites
+    pub exclude_domains: Vec<String>,
+    pub topic: Topic,
+    pub search_depth: SearchDepth,
+}
+
+impl Default for SearchOptions {
+    fn default() -> Self {
+        Self {
+            max_results: 5,
+            time_range: None,
+            include_domains: Vec::new(),
+            exclude_domains: Vec::new(),
+            topic: Topic::default(),
+            search_depth: SearchDepth::default(),
+        }
+    }
+}
+
+impl SearchOptions {
+    /// Check the options before sending them to a provider
+    ///
+    /// # Errors
+    /// Returns [`SearchError::InvalidOptions`] describing the first invalid option.
+    pub fn validate(&self) -> Result<(), SearchError> {
+        if !(1..=MAX_RESULTS).contains(&self.max_results) {
+            return Err(SearchError::InvalidOptions(format!(
+                "max_results must be between 1 and {}, got {}",
+                MAX_RESULTS, self.max_results
+            )));
+        }
+        for domain in self.include_domains.iter().chain(&self.exclude_domains) {
+            if !is_domain(domain) {
+                return Err(SearchError::InvalidOptions(format!(
+                    "\"{}\" is not a domain name (expected e.g. \"example.com\")",
+                    domain
+                )));
+            }
+        }
+        if let Some(domain) = self
+            .include_domains
+            .iter()
+            .find(|domain| self.exclude_domains.contains(domain))
+        {
+            return Err(SearchError::InvalidOptions(format!(
+                "{} is both included and excluded",
+                domain
+            )));
+        }
+        Ok(())
+    }
+
+    /// `query` with `site:` operators for the domain filters, for backends that
+    /// have no separate parameters for them
+    fn query_with_sites(&self, query: &str) -> String {
+        let mut terms = vec![query.to_string()];
+        match self.include_domains.as_slice() {
+            [] => {}
+            [domain] => terms.push(format!("site:{}", domain)),
+            domains => terms.push(format!(
+                "({})",
+                domains
+                    .iter()
+                    .map(|domain| format!("site:{}", domain))
+                    .collect::<Vec<_>>()
+                    .join(" OR ")
+            )),
+        }
+        terms.extend(
+            self.exclude_domains
+                .iter()
+                .map(|domain| format!("-site:{}", domain)),
+        );
+        terms.join(" ")
+    }
+}
+
+/// A bare host name such as `example.com`, without scheme, port or path
+fn is_domain(domain: &str) -> bool {
+    domain.contains('.')
+        && domain.split('.').all(|label| {
+            !label.is_empty()
+                && !label.starts_with('-')
+                && !label.ends_with('-')
+                && label.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+        })
+}
+
+/// Error returned by a search provider
+#[derive(Debug, thiserror::Error)]
+pub enum SearchError {
+    #[error("API key not found: set {0} environment variable")]
+    ApiKeyNotFound(&'static str),
+    #[error("Invalid search options: {0}")]
+    InvalidOptions(String),
+    #[error("The search API rejected the API key (HTTP {0})")]
+    Unauthorized(StatusCode),
+    #[error("Search quota or rate limit exceeded{}", retry_hint(.retry_after))]
+    RateLimited { retry_after: Option<Duration> },
+    #[error("The search API failed (HTTP {0})")]
+    ServerError(StatusCode),
+    #[error("The search API returned HTTP {0}")]
+    HttpStatus(StatusCode),
+    #[error("The search API returned a malformed response: {0}")]
+    InvalidResponse(#[source] serde_json::Error),
+    #[error("The search API returned more than {0} bytes")]
+    ResponseTooLarge(usize),
+    #[error("The search API did not answer in time")]
+    Timeout,
+    #[error(transparent)]
+    Request(#[from] anyhow::Error),
+}
+
+fn retry_hint(retry_after: &Option<Duration>) -> String {
+    match retry_after {
+        Some(delay) => format!("; retry after {} s", delay.as_secs()),
+        None => String::new(),
+    }
+}
+
+impl From<reqwest::Error> for SearchError {
+    fn from(error: reqwest::Error) -> Self {
+        if error.is_timeout() {
+            Self::Timeout
+        } else {
+            Self::Request(error.into())
+        }
+    }
+}
+
+/// A web search backend
+pub trait SearchProvider: Send + Sync {
+    /// Name of the backend, for logs
+    fn name(&self) -> &'static str;
+
+    /// Search the web for `query`, returning at most `options.max_results` results,
+    /// best first
+    fn search<'a>(
+        &'a self,
+        query: &'a str,
+        options: &'a SearchOptions,
+    ) -> BoxFuture<'a, Result<Vec<SearchResult>, SearchError>>;
+}
+
+/// Which search backend to use, from the `[search]` table of the config file.
+/// API keys are read from the environment.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(tag = "provider", rename_all = "lowercase", deny_unknown_fields)]
+pub enum SearchConfig {
+    /// Tavily API (`TAVILY_API_KEY`)
+    #[default]
+    Tavily,
+    /// Self-hosted SearXNG instance with the JSON format enabled
+    Searxng { url: String },
+    /// Brave Search API (`BRAVE_API_KEY`)
+    Brave,
+    /// DuckDuckGo's HTML results page, no key needed
+    DuckDuckGo,
+}
+
+impl SearchConfig {
+    /// Build the configured provider on top of a shared HTTP client
+    pub fn provider(
+        &self,
+        client: ReqwestClient,
+        retry_policy: RetryPolicy,
+    ) -> Arc<dyn SearchProvider> {
+        let http = SearchHttp::new(client, retry_policy);
+        match self {
+            Self::Tavily => Arc::new(Tavily::new(http, env::var("TAVILY_API_KEY").ok())),
+            Self::Searxng { url } => Arc::new(Searxng::new(http, url)),
+            Self::Brave => Arc::new(Brave::new(http, env::var("BRAVE_API_KEY").ok())),
+            Self::DuckDuckGo => Arc::new(DuckDuckGo::new(http)),
+        }
+    }
+}
+
+/// HTTP client shared by the providers, retrying transient failures
+#[derive(Clone)]
+pub(crate) struct SearchHttp {
+    client: ReqwestClient,
+    retry_policy: RetryPolicy,
+}
+
+impl SearchHttp {
+    pub(crate) fn new(client: ReqwestClient, retry_policy: RetryPolicy) -> Self {
+        Self {
+            client,
+            retry_policy,
+        }
+    }
+
+    /// Send the request built by `request`, rebuilding it for each retry, and
+    /// turn error statuses into typed errors. Requests that aren't idempotent
+    /// (POSTs) aren't retried when the server may have acted on them.
+    async fn send(
+        &self,
+        target: &str,
+        request: impl Fn(&reqwest::Client) -> reqwest::RequestBuilder,
+    ) -> Result<reqwest::Response, SearchError> {
+        let idempotent = request(self.client.inner())
+            .build()
+            .is_ok_and(|request| request.method().is_idempotent());
+        let response = if idempotent {
+            self.retry_policy
+                .run(target, || request(self.client.inner()).send())
+                .await?
+        } else {
+            self.retry_policy
+                .run(target, || async {
+                    NotIdempotent(request(self.client.inner()).send().await)
+                })
+                .await
+                .0?
+        };
+        let status = response.status();
+        match status {
+            _ if status.is_success() => Ok(response),
+            StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN => {
+                Err(SearchError::Unauthorized(status))
+            }
+            StatusCode::TOO_MANY_REQUESTS => Err(SearchError::RateLimited {
+                retry_after: retry::retry_after(response.headers()),
+            }),
+            _ if status.is_server_error() => Err(SearchError::ServerError(status)),
+            _ => Err(SearchError::HttpStatus(status)),
+        }
+    }
+
+    /// Read a response body of at most `MAX_RESPONSE_BYTES`, so that a misbehaving
+    /// instance or a captive portal can't make us buffer any amount of data
+    async fn read(response: reqwest::Response) -> Result<HttpResponse, SearchError> {
+        let response = read_response(response, |_| MAX_RESPONSE_BYTES)
+            .await
+            .map_err(|e| match e.downcast::<reqwest::Error>() {
+                Ok(e) => SearchError::from(e),
+                Err(e) if e.is::<FetchError>() => SearchError::ResponseTooLarge(MAX_RESPONSE_BYTES),
+                Err(e) => SearchError::Request(e),
+            })?;
+        if response.truncated {
+            return Err(SearchError::ResponseTooLarge(MAX_RESPONSE_BYTES));
+        }
+        Ok(response)
+    }
+
+    /// Read a JSON response body into `T`
+    async fn json<T: DeserializeOwned>(response: reqwest::Response) -> Result<T, SearchError> {
+        let response = Self::read(response).await?;
+        serde_json::from_slice(&response.body).map_err(SearchError::InvalidResponse)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn options(include: &[&str], exclude: &[&str]) -> SearchOptions {
+        SearchOptions {
+            include_domains: include.iter().map(|d| d.to_string()).collect(),
+            exclude_domains: exclude.iter().map(|d| d.to_string()).collect(),
+            ..SearchOptions::default()
+        }
+    }
+
+    #[test]
+    fn test_options_deserialize_with_defaults() {
+        let options: SearchOptions =
+            serde_json::from_str(r#"{"time_range": "week", "topic": "news"}"#).unwrap();
+
+        assert_eq!(options.max_results, 5);
+        assert_eq!(options.time_range, Some(TimeRange::Week));
+        assert_eq!(options.topic, Topic::News);
+        assert_eq!(options.search_depth, SearchDepth::Basic);
+        assert!(serde_json::from_str::<SearchOptions>(r#"{"time_range": "decade"}"#).is_err());
+    }
+
+    #[test]
+    fn test_validate_options() {
+        assert!(options(&["docs.rs", "rust-lang.org"], &["reddit.com"])
+            .validate()
+            .is_ok());
+        for max_results in [0, MAX_RESULTS + 1] {
+            let options = SearchOptions {
+                max_results,
+                ..SearchOptions::default()
+            };
+            assert!(matches!(
+                options.validate(),
+                Err(SearchError::InvalidOptions(_))
+            ));
+        }
+        assert!(options(&["https://docs.rs/tokio"], &[]).validate().is_err());
+        assert!(options(&[], &["localhost"]).validate().is_err());
+        assert!(options(&["docs.rs"], &["docs.rs"]).validate().is_err());
+    }
+
+    #[test]
+    fn test_query_with_sites() {
+        assert_eq!(options(&[], &[]).query_with_sites("tokio"), "tokio");
+        assert_eq!(
+            options(&["docs.rs"], &["reddit.com"]).query_with_sites("tokio"),
+            "tokio site:docs.rs -site:reddit.com"
+        );
+        assert_eq!(
+            options(&["docs.rs", "tokio.rs"], &[]).query_with_sites("tokio"),
+            "tokio (site:docs.rs OR site:tokio.rs)"
+        );
+    }
+}
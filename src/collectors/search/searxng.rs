@@ -0,0 +1,203 @@
+use futures::future::BoxFuture;
+use serde::Deserialize;
+
+use super::{SearchError, SearchHttp, SearchOptions, SearchProvider, SearchResult, Topic};
+
+/// Search through a SearXNG instance (`search.formats` must include `json`)
+pub struct Searxng {
+    http: SearchHttp,
+    /// Search endpoint, e.g. "http://localhost:8888/search"
+    endpoint: String,
+}
+
+#[derive(Deserialize)]
+struct SearxngResponse {
+    #[serde(default)]
+    results: Vec<SearxngResult>,
+}
+
+#[derive(Deserialize)]
+struct SearxngResult {
+    title: String,
+    url: String,
+    #[serde(default)]
+    content: String,
+}
+
+impl Searxng {
+    /// `base_url` is the instance's root URL, e.g. "http://localhost:8888"
+    pub(crate) fn new(http: SearchHttp, base_url: &str) -> Self {
+        Self {
+            http,
+            endpoint: format!("{}/search", base_url.trim_end_matches('/')),
+        }
+    }
+
+    async fn search(
+        &self,
+        query: &str,
+        options: &SearchOptions,
+    ) -> Result<Vec<SearchResult>, SearchError> {
+        let query = options.query_with_sites(query);
+        let category = match options.topic {
+            Topic::General => "general",
+            Topic::News => "news",
+        };
+        let mut params = vec![
+            ("q", query.as_str()),
+            ("format", "json"),
+            ("categories", category),
+        ];
+        if let Some(time_range) = options.time_range {
+            params.push(("time_range", time_range.as_str()));
+        }
+        let response = self
+            .http
+            .send(&self.endpoint, |client| {
+                client.get(&self.endpoint).query(&params)
+            })
+            .await?;
+        let response: SearxngResponse = SearchHttp::json(response).await?;
+
+        // SearXNG has no result count parameter
+        Ok(response
+            .results
+            .into_iter()
+            .take(options.max_results)
+            .map(|result| SearchResult {
+                title: result.title,
+                url: result.url,
+                snippet: result.content,
+            })
+            .collect())
+    }
+}
+
+impl SearchProvider for Searxng {
+    fn name(&self) -> &'static str {
+        "searxng"
+    }
+
+    fn search<'a>(
+        &'a self,
+        query: &'a str,
+        options: &'a SearchOptions,
+    ) -> BoxFuture<'a, Result<Vec<SearchResult>, SearchError>> {
+        Box::pin(Searxng::search(self, query, options))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::collectors::retry::RetryPolicy;
+    use crate::collectors::search::{TimeRange, MAX_RESPONSE_BYTES};
+    use crate::collectors::web::{HttpConfig, ReqwestClient};
+    use wiremock::matchers::{method, path, query_param, query_param_is_missing};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    #[tokio::test]
+    async fn test_search_parses_recorded_response() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/search"))
+            .and(query_param("q", "rust async runtime"))
+            .and(query_param("format", "json"))
+            .and(query_param("categories", "general"))
+            .and(query_param_is_missing("time_range"))
+            .respond_with(ResponseTemplate::new(200).set_body_raw(
+                include_str!("../../../tests/fixtures/search/searxng.json"),
+                "application/json",
+            ))
+            .mount(&server)
+            .await;
+        let client = ReqwestClient::new(&HttpConfig::default()).unwrap();
+        let searxng = Searxng::new(
+            SearchHttp::new(client, RetryPolicy::default()),
+            &format!("{}/", server.uri()),
+        );
+
+        let results = searxng
+            .search("rust async runtime", &SearchOptions::default())
+            .await
+            .unwrap();
+
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].url, "https://tokio.rs/");
+        assert!(results[0].snippet.starts_with("Tokio is an event-driven"));
+        // Results without content get an empty snippet
+        assert_eq!(results[1].title, "async_std - Rust");
+        assert_eq!(results[1].snippet, "");
+
+        let options = SearchOptions {
+            max_results: 1,
+            ..SearchOptions::default()
+        };
+        let first = searxng
+            .search("rust async runtime", &options)
+            .await
+            .unwrap();
+        assert_eq!(first.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_search_maps_options_to_parameters() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/search"))
+            .and(query_param(
+                "q",
+                "tokio release site:tokio.rs -site:reddit.com",
+            ))
+            .and(query_param("categories", "news"))
+            .and(query_param("time_range", "month"))
+            .respond_with(ResponseTemplate::new(200).set_body_raw(
+                include_str!("../../../tests/fixtures/search/searxng.json"),
+                "application/json",
+            ))
+            .expect(1)
+            .mount(&server)
+            .await;
+        let client = ReqwestClient::new(&HttpConfig::default()).unwrap();
+        let searxng = Searxng::new(
+            SearchHttp::new(client, RetryPolicy::default()),
+            &server.uri(),
+        );
+        let options = SearchOptions {
+            time_range: Some(TimeRange::Month),
+            include_domains: vec!["tokio.rs".to_string()],
+            exclude_domains: vec!["reddit.com".to_string()],
+            topic: Topic::News,
+            ..SearchOptions::default()
+        };
+
+        let results = searxng.search("tokio release", &options).await.unwrap();
+
+        assert_eq!(results.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_search_refuses_oversized_response() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/search"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_raw(vec![b' '; MAX_RESPONSE_BYTES + 1], "application/json"),
+            )
+            .mount(&server)
+            .await;
+        let client = ReqwestClient::new(&HttpConfig::default()).unwrap();
+        let searxng = Searxng::new(
+            SearchHttp::new(client, RetryPolicy::default()),
+            &server.uri(),
+        );
+
+        let result = searxng.search("tokio", &SearchOptions::default()).await;
+
+        assert!(matches!(
+            result,
+            Err(SearchError::ResponseTooLarge(MAX_RESPONSE_BYTES))
+        ));
+    }
+}
@@ -0,0 +1,332 @@
+use futures::future::BoxFuture;
+use serde::{Deserialize, Serialize};
+
+use super::{
+    SearchDepth, SearchError, SearchHttp, SearchOptions, SearchProvider, SearchResult, TimeRange,
+    Topic,
+};
+
+const ENDPOINT: &str = "https://api.tavily.com/search";
+
+/// Search through the Tavily API
+pub struct Tavily {
+    http: SearchHttp,
+    api_key: Option<String>,
+    endpoint: String,
+}
+
+#[derive(Serialize)]
+struct TavilyRequest<'a> {
+    api_key: &'a str,
+    query: &'a str,
+    max_results: usize,
+    topic: Topic,
+    search_depth: SearchDepth,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    time_range: Option<TimeRange>,
+    #[serde(skip_serializing_if = "<[_]>::is_empty")]
+    include_domains: &'a [String],
+    #[serde(skip_serializing_if = "<[_]>::is_empty")]
+    exclude_domains: &'a [String],
+}
+
+#[derive(Deserialize)]
+struct TavilyResponse {
+    #[serde(default)]
+    results: Vec<TavilyResult>,
+}
+
+#[derive(Deserialize)]
+struct TavilyResult {
+    title: String,
+    url: String,
+    #[serde(default)]
+    content: String,
+}
+
+impl Tavily {
+    pub(crate) fn new(http: SearchHttp, api_key: Option<String>) -> Self {
+        Self {
+            http,
+            api_key,
+            endpoint: ENDPOINT.to_string(),
+        }
+    }
+
+    #[cfg(test)]
+    fn with_endpoint(mut self, endpoint: String) -> Self {
+        self.endpoint = endpoint;
+        self
+    }
+
+    async fn search(
+        &self,
+        query: &str,
+        options: &SearchOptions,
+    ) -> Result<Vec<SearchResult>, SearchError> {
+        let api_key = self
+            .api_key
+            .as_deref()
+            .ok_or(SearchError::ApiKeyNotFound("TAVILY_API_KEY"))?;
+        let body = TavilyRequest {
+            api_key,
+            query,
+            max_results: options.max_results,
+            topic: options.topic,
+            search_depth: options.search_depth,
+            time_range: options.time_range,
+            include_domains: &options.include_domains,
+            exclude_domains: &options.exclude_domains,
+        };
+        let response = self
+            .http
+            .send(&self.endpoint, |client| {
+                client.post(&self.endpoint).json(&body)
+            })
+            .await?;
+        let response: TavilyResponse = SearchHttp::json(response).await?;
+
+        Ok(response
+            .results
+            .into_iter()
+            .map(|result| SearchResult {
+                title: result.title,
+                url: result.url,
+                snippet: result.content,
+            })
+            .collect())
+    }
+}
+
+impl SearchProvider for Tavily {
+    fn name(&self) -> &'static str {
+        "tavily"
+    }
+
+    fn search<'a>(
+        &'a self,
+        query: &'a str,
+        options: &'a SearchOptions,
+    ) -> BoxFuture<'a, Result<Vec<SearchResult>, SearchError>> {
+        Box::pin(Tavily::search(self, query, options))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::collectors::retry::RetryPolicy;
+    use crate::collectors::web::{HttpConfig, ReqwestClient};
+    use reqwest::StatusCode;
+    use std::time::Duration;
+    use wiremock::matchers::{body_partial_json, method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    /// Tavily client for the stand-in server, without retries and with a short timeout
+    fn tavily(server: &MockServer, api_key: Option<&str>) -> Tavily {
+        let config = HttpConfig {
+            timeout: Duration::from_millis(200),
+            ..HttpConfig::default()
+        };
+        let retry_policy = RetryPolicy {
+            max_attempts: 1,
+            ..RetryPolicy::default()
+        };
+        let http = SearchHttp::new(ReqwestClient::new(&config).unwrap(), retry_policy);
+        Tavily::new(http, api_key.map(str::to_string))
+            .with_endpoint(format!("{}/search", server.uri()))
+    }
+
+    async fn search_answered_with(
+        response: ResponseTemplate,
+    ) -> Result<Vec<SearchResult>, SearchError> {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/search"))
+            .respond_with(response)
+            .mount(&server)
+            .await;
+        tavily(&server, Some("tvly-test"))
+            .search("rust", &SearchOptions::default())
+            .await
+    }
+
+    #[tokio::test]
+    async fn test_search_does_not_resend_timed_out_request() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/search"))
+            .respond_with(ResponseTemplate::new(200).set_delay(Duration::from_secs(5)))
+            .mount(&server)
+            .await;
+        let config = HttpConfig {
+            timeout: Duration::from_millis(200),
+            ..HttpConfig::default()
+        };
+        let retry_policy = RetryPolicy {
+            max_attempts: 3,
+            base_delay: Duration::from_millis(1),
+            ..RetryPolicy::default()
+        };
+        let http = SearchHttp::new(ReqwestClient::new(&config).unwrap(), retry_policy);
+        let tavily = Tavily::new(http, Some("tvly-test".to_string()))
+            .with_endpoint(format!("{}/search", server.uri()));
+
+        let result = tavily.search("rust", &SearchOptions::default()).await;
+
+        assert!(matches!(result, Err(SearchError::Timeout)));
+        assert_eq!(server.received_requests().await.unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_search_parses_recorded_response() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/search"))
+            .and(body_partial_json(serde_json::json!({
+                "api_key": "tvly-test",
+                "query": "rust async runtime",
+                "max_results": 5,
+                "topic": "general",
+                "search_depth": "basic",
+            })))
+            .respond_with(ResponseTemplate::new(200).set_body_raw(
+                include_str!("../../../tests/fixtures/search/tavily.json"),
+                "application/json",
+            ))
+            .mount(&server)
+            .await;
+
+        let results = tavily(&server, Some("tvly-test"))
+            .search("rust async runtime", &SearchOptions::default())
+            .await
+            .unwrap();
+
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].title, "Tokio - An asynchronous Rust runtime");
+        assert_eq!(results[0].url, "https://tokio.rs/");
+        assert!(results[0].snippet.starts_with("Tokio is an event-driven"));
+        assert_eq!(results[1].url, "https://rust-lang.github.io/async-book/");
+    }
+
+    #[tokio::test]
+    async fn test_search_sends_options() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/search"))
+            .respond_with(ResponseTemplate::new(200).set_body_raw(
+                include_str!("../../../tests/fixtures/search/tavily.json"),
+                "application/json",
+            ))
+            .mount(&server)
+            .await;
+        let options = SearchOptions {
+            max_results: 10,
+            time_range: Some(TimeRange::Day),
+            include_domains: vec!["tokio.rs".to_string()],
+            exclude_domains: vec!["reddit.com".to_string()],
+            topic: Topic::News,
+            search_depth: SearchDepth::Advanced,
+        };
+
+        tavily(&server, Some("tvly-test"))
+            .search("tokio release", &options)
+            .await
+            .unwrap();
+
+        let requests = server.received_requests().await.unwrap();
+        let body: serde_json::Value = requests[0].body_json().unwrap();
+        assert_eq!(
+            body,
+            serde_json::json!({
+                "api_key": "tvly-test",
+                "query": "tokio release",
+                "max_results": 10,
+                "topic": "news",
+                "search_depth": "advanced",
+                "time_range": "day",
+                "include_domains": ["tokio.rs"],
+                "exclude_domains": ["reddit.com"],
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn test_search_without_api_key_fails_without_request() {
+        let server = MockServer::start().await;
+
+        let result = tavily(&server, None)
+            .search("rust", &SearchOptions::default())
+            .await;
+
+        assert!(matches!(
+            result,
+            Err(SearchError::ApiKeyNotFound("TAVILY_API_KEY"))
+        ));
+        assert!(server.received_requests().await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_bad_api_key_is_unauthorized() {
+        let response = ResponseTemplate::new(401)
+            .set_body_raw(r#"{"detail":{"error":"Unauthorized"}}"#, "application/json");
+
+        let result = search_answered_with(response).await;
+
+        assert!(matches!(
+            result,
+            Err(SearchError::Unauthorized(StatusCode::UNAUTHORIZED))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_exhausted_quota_is_rate_limited() {
+        let response = ResponseTemplate::new(429).insert_header("Retry-After", "60");
+
+        let result = search_answered_with(response).await;
+
+        match result {
+            Err(SearchError::RateLimited { retry_after }) => {
+                assert_eq!(retry_after, Some(Duration::from_secs(60)));
+            }
+            other => panic!("expected RateLimited, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_html_error_page_is_server_error() {
+        let response = ResponseTemplate::new(502)
+            .set_body_raw("<html><body>Bad Gateway</body></html>", "text/html");
+
+        let result = search_answered_with(response).await;
+
+        assert!(matches!(
+            result,
+            Err(SearchError::ServerError(StatusCode::BAD_GATEWAY))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_malformed_json_is_invalid_response() {
+        let response =
+            ResponseTemplate::new(200).set_body_raw(r#"{"results": [{"title"#, "application/json");
+
+        let result = search_answered_with(response).await;
+
+        assert!(matches!(result, Err(SearchError::InvalidResponse(_))));
+    }
+
+    #[tokio::test]
+    async fn test_slow_answer_is_timeout() {
+        let response = ResponseTemplate::new(200)
+            .set_body_raw(
+                include_str!("../../../tests/fixtures/search/tavily.json"),
+                "application/json",
+            )
+            .set_delay(Duration::from_secs(2));
+
+        let result = search_answered_with(response).await;
+
+        assert!(matches!(result, Err(SearchError::Timeout)));
+    }
+}
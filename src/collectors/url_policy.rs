@@ -0,0 +1,329 @@
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+
+use anyhow::{Context, Result};
+use reqwest::dns::{Addrs, Name, Resolve, Resolving};
+use reqwest::Url;
+
+use super::web::HttpClient;
+
+/// Why a URL may not be fetched
+#[derive(Debug, thiserror::Error)]
+pub enum UrlBlocked {
+    #[error("{0} is not a valid URL")]
+    InvalidUrl(String),
+    #[error("{url} can't be fetched: only http and https URLs are allowed")]
+    UnsupportedScheme { url: String },
+    #[error("{url} can't be fetched: {host} is a private or reserved address ({addr})")]
+    PrivateAddress {
+        url: String,
+        host: String,
+        addr: IpAddr,
+    },
+    #[error("Refused to connect to {host}: it resolves to a private or reserved address ({addr})")]
+    PrivateConnection { host: String, addr: IpAddr },
+}
+
+/// Which URLs may be fetched: http(s) URLs whose host resolves only to public
+/// addresses, plus explicitly allowed hosts (e.g. a local server during development).
+///
+/// [`check`](Self::check) rejects a URL before it is requested. The host is resolved
+/// again when connecting, so a client must also resolve through a [`PolicyResolver`]
+/// for a DNS server answering differently the second time not to slip through.
+#[derive(Debug, Clone, Default)]
+pub struct UrlPolicy {
+    /// Hosts (`localhost`) or hosts with a port (`127.0.0.1:8080`) exempt from the
+    /// address check, lowercased
+    allowed_hosts: Vec<String>,
+}
+
+impl UrlPolicy {
+    /// Allow fetching from `host` even if it is private, e.g. `localhost:8080`.
+    /// Without a port, every port of the host is allowed.
+    pub fn allow_host(mut self, host: &str) -> Self {
+        self.allowed_hosts.push(host.to_ascii_lowercase());
+        self
+    }
+
+    /// Check `url` before requesting it, resolving its host through `client`
+    ///
+    /// # Errors
+    /// Returns a `UrlBlocked` error (inside `anyhow`) if the URL is not allowed,
+    /// or an error if the host can't be resolved.
+    pub(crate) async fn check<C: HttpClient>(&self, client: &C, url: &str) -> Result<()> {
+        let parsed = Url::parse(url).map_err(|_| UrlBlocked::InvalidUrl(url.to_string()))?;
+        if !matches!(parsed.scheme(), "http" | "https") {
+            return Err(UrlBlocked::UnsupportedScheme {
+                url: url.to_string(),
+            }
+            .into());
+        }
+        let Some(host) = parsed.host_str() else {
+            return Err(UrlBlocked::InvalidUrl(url.to_string()).into());
+        };
+        let port = parsed.port_or_known_default().unwrap_or(80);
+        if self.is_allowed_host(host, port) {
+            return Ok(());
+        }
+
+        // IPv6 literals keep their brackets in the URL
+        let addrs = match host.trim_start_matches('[').trim_end_matches(']').parse() {
+            Ok(addr) => vec![addr],
+            Err(_) => client
+                .resolve(host, port)
+                .await
+                .with_context(|| format!("Failed to resolve {}", host))?,
+        };
+        match addrs.into_iter().find(|addr| is_blocked_addr(*addr)) {
+            Some(addr) => Err(UrlBlocked::PrivateAddress {
+                url: url.to_string(),
+                host: host.to_string(),
+                addr,
+            }
+            .into()),
+            None => Ok(()),
+        }
+    }
+
+    fn is_allowed_host(&self, host: &str, port: u16) -> bool {
+        let host = host.to_ascii_lowercase();
+        let host_port = format!("{}:{}", host, port);
+        self.allowed_hosts
+            .iter()
+            .any(|allowed| *allowed == host || *allowed == host_port)
+    }
+
+    /// Whether `host` is allowed on some port; a resolver doesn't know the port
+    fn is_allowed_on_any_port(&self, host: &str) -> bool {
+        let host = host.to_ascii_lowercase();
+        self.allowed_hosts.iter().any(|allowed| {
+            allowed
+                .strip_prefix(host.as_str())
+                .is_some_and(|rest| rest.is_empty() || rest.starts_with(':'))
+        })
+    }
+}
+
+type BoxError = Box<dyn std::error::Error + Send + Sync>;
+
+/// DNS resolver for reqwest refusing host names that resolve to a blocked address,
+/// so that the addresses a request connects to are the checked ones. IP literals
+/// are never resolved; [`UrlPolicy::check`] covers them.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct PolicyResolver {
+    policy: UrlPolicy,
+}
+
+impl PolicyResolver {
+    pub(crate) fn new(policy: UrlPolicy) -> Self {
+        Self { policy }
+    }
+
+    async fn lookup(&self, host: &str) -> Result<Vec<SocketAddr>, BoxError> {
+        let addrs: Vec<SocketAddr> = tokio::net::lookup_host((host, 0)).await?.collect();
+        if self.policy.is_allowed_on_any_port(host) {
+            return Ok(addrs);
+        }
+        match addrs.iter().find(|addr| is_blocked_addr(addr.ip())) {
+            Some(addr) => Err(Box::new(UrlBlocked::PrivateConnection {
+                host: host.to_string(),
+                addr: addr.ip(),
+            })),
+            None => Ok(addrs),
+        }
+    }
+}
+
+impl Resolve for PolicyResolver {
+    fn resolve(&self, name: Name) -> Resolving {
+        let resolver = self.clone();
+        Box::pin(async move {
+            let addrs = resolver.lookup(name.as_str()).await?;
+            Ok(Box::new(addrs.into_iter()) as Addrs)
+        })
+    }
+}
+
+/// Loopback, private (RFC 1918, unique local), link-local (incl. cloud metadata
+/// endpoints), shared, unspecified, multicast and reserved addresses
+fn is_blocked_addr(addr: IpAddr) -> bool {
+    match addr {
+        IpAddr::V4(ip) => is_blocked_ipv4(ip),
+        IpAddr::V6(ip) => match ip.to_ipv4_mapped() {
+            Some(mapped) => is_blocked_ipv4(mapped),
+            None => is_blocked_ipv6(ip),
+        },
+    }
+}
+
+fn is_blocked_ipv4(ip: Ipv4Addr) -> bool {
+    let [a, b, ..] = ip.octets();
+    ip.is_loopback()
+        || ip.is_private()
+        || ip.is_link_local()
+        || ip.is_unspecified()
+        || ip.is_broadcast()
+        || ip.is_multicast()
+        // "This network" 0.0.0.0/8
+        || a == 0
+        // Shared address space 100.64.0.0/10 (carrier-grade NAT, some metadata services)
+        || (a == 100 && (b & 0b1100_0000) == 64)
+        // IETF protocol assignments 192.0.0.0/24
+        || (a == 192 && b == 0 && ip.octets()[2] == 0)
+        // Benchmarking 198.18.0.0/15
+        || (a == 198 && (b & 0xfe) == 18)
+        // Reserved 240.0.0.0/4
+        || a >= 240
+}
+
+fn is_blocked_ipv6(ip: Ipv6Addr) -> bool {
+    let segments = ip.segments();
+    let octets = ip.octets();
+    ip.is_loopback()
+        || ip.is_unspecified()
+        || ip.is_multicast()
+        || ip.is_unique_local()
+        || ip.is_unicast_link_local()
+        // Site-local fec0::/10 (deprecated)
+        || (segments[0] & 0xffc0) == 0xfec0
+        // IPv4-compatible ::a.b.c.d (deprecated)
+        || segments[..6] == [0; 6]
+        // NAT64 64:ff9b::/96, reaching the embedded IPv4 address
+        || (segments[..6] == [0x64, 0xff9b, 0, 0, 0, 0]
+            && is_blocked_ipv4(Ipv4Addr::new(octets[12], octets[13], octets[14], octets[15])))
+        // 6to4 2002::/16, reaching the IPv4 address in the next 32 bits
+        || (segments[0] == 0x2002
+            && is_blocked_ipv4(Ipv4Addr::new(octets[2], octets[3], octets[4], octets[5])))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::collectors::web::HttpResponse;
+
+    /// Client resolving every host name to a fixed address
+    struct Resolver(IpAddr);
+
+    impl HttpClient for Resolver {
+        async fn get(&self, _url: &str) -> Result<HttpResponse> {
+            unreachable!("the policy never sends requests")
+        }
+
+        async fn resolve(&self, _host: &str, _port: u16) -> Result<Vec<IpAddr>> {
+            Ok(vec![self.0])
+        }
+    }
+
+    fn public() -> Resolver {
+        Resolver("93.184.215.14".parse().unwrap())
+    }
+
+    async fn is_blocked(policy: &UrlPolicy, client: &Resolver, url: &str) -> bool {
+        match policy.check(client, url).await {
+            Ok(()) => false,
+            Err(e) => e.is::<UrlBlocked>(),
+        }
+    }
+
+    #[test]
+    fn test_blocked_addresses() {
+        for addr in [
+            "127.0.0.1",
+            "10.1.2.3",
+            "172.16.0.1",
+            "192.168.1.1",
+            "169.254.169.254",
+            "100.100.100.200",
+            "0.0.0.0",
+            "::1",
+            "fe80::1",
+            "fd00:ec2::254",
+            "::ffff:127.0.0.1",
+            "fec0::1",
+            "64:ff9b::a9fe:a9fe",
+            "64:ff9b::7f00:1",
+            "2002:a00:1::1",
+            "2002:c0a8:101::",
+        ] {
+            assert!(is_blocked_addr(addr.parse().unwrap()), "{}", addr);
+        }
+        for addr in [
+            "93.184.215.14",
+            "8.8.8.8",
+            "172.32.0.1",
+            "2606:4700::1111",
+            "64:ff9b::808:808",
+            "2002:808:808::1",
+        ] {
+            assert!(!is_blocked_addr(addr.parse().unwrap()), "{}", addr);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_blocks_private_ip_literals() {
+        let policy = UrlPolicy::default();
+
+        assert!(
+            is_blocked(
+                &policy,
+                &public(),
+                "http://169.254.169.254/latest/meta-data/"
+            )
+            .await
+        );
+        assert!(is_blocked(&policy, &public(), "http://[::1]:8080/admin").await);
+        assert!(!is_blocked(&policy, &public(), "https://93.184.215.14/").await);
+    }
+
+    #[tokio::test]
+    async fn test_blocks_host_names_resolving_to_private_addresses() {
+        let policy = UrlPolicy::default();
+        let internal = Resolver("10.0.0.5".parse().unwrap());
+
+        assert!(is_blocked(&policy, &internal, "https://intranet.example.com/").await);
+        assert!(!is_blocked(&policy, &public(), "https://example.com/").await);
+    }
+
+    #[tokio::test]
+    async fn test_blocks_non_http_schemes() {
+        let policy = UrlPolicy::default();
+
+        for url in [
+            "file:///etc/passwd",
+            "ftp://example.com/file",
+            "gopher://example.com",
+        ] {
+            let err = policy.check(&public(), url).await.unwrap_err();
+            assert!(matches!(
+                err.downcast_ref::<UrlBlocked>(),
+                Some(UrlBlocked::UnsupportedScheme { .. })
+            ));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_resolver_refuses_hosts_resolving_to_private_addresses() {
+        let resolver = PolicyResolver::default();
+        let allowing = PolicyResolver::new(UrlPolicy::default().allow_host("localhost:8080"));
+
+        let err = resolver.lookup("localhost").await.unwrap_err();
+
+        assert!(matches!(
+            err.downcast_ref::<UrlBlocked>(),
+            Some(UrlBlocked::PrivateConnection { .. })
+        ));
+        assert!(!allowing.lookup("localhost").await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_allowlisted_hosts_skip_address_check() {
+        let policy = UrlPolicy::default()
+            .allow_host("LOCALHOST")
+            .allow_host("127.0.0.1:8080");
+        let loopback = Resolver("127.0.0.1".parse().unwrap());
+
+        assert!(!is_blocked(&policy, &loopback, "http://localhost:3000/").await);
+        assert!(!is_blocked(&policy, &loopback, "http://127.0.0.1:8080/").await);
+        assert!(is_blocked(&policy, &loopback, "http://127.0.0.1:9090/").await);
+        assert!(is_blocked(&policy, &loopback, "file://localhost/etc/passwd").await);
+    }
+}
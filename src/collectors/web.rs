@@ -1,9 +1,28 @@
-use anyhow::{bail, Ok, Result};
-use reqwest;
+use std::net::IpAddr;
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use log::{info, warn};
+use reqwest::header::{HeaderMap, CONTENT_LENGTH, CONTENT_TYPE, LOCATION};
+use reqwest::redirect::Policy;
+use reqwest::{Proxy, StatusCode, Url};
 use scraper::{Html, Selector};
 use serde::{Deserialize, Serialize};
 
+use super::domain_policy::{DomainBlocked, DomainPolicy};
+use super::encoding::{charset_param, decode_html, decode_text};
+use super::http_cache::{validators, CacheLookup, HttpCache};
+use super::links::{document_base_url, extract_links, Link};
+use super::markdown::to_markdown;
+use super::metadata::{extract_metadata, PageMetadata};
+use super::opt_out::{header_directives, meta_directives, opt_outs, OptOutPolicy};
+use super::pdf::{format_pages, number_pages, parse_pdf};
+use super::politeness::{HostScheduler, PolitenessConfig};
+use super::readability::{extract_main_content, to_text};
+use super::retry::RetryPolicy;
 use super::robots::RobotsCache;
+use super::url_policy::{PolicyResolver, UrlBlocked, UrlPolicy};
 
 /// Represents parsed content from a web page
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -14,52 +33,771 @@ pub struct PageContent {
     pub title: Option<String>,
     /// The main text content of the page
     pub text: String,
+    /// Text of each page of a paged document (PDF), first page first; `text` then
+    /// holds them all after page markers
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub pages: Vec<String>,
+    /// Canonical URL, author, publication date, etc. (if declared by the page)
+    pub metadata: PageMetadata,
+    /// Deduplicated absolute links found on the page
+    pub links: Vec<Link>,
+    /// Whether the page was cut because its body exceeded the size limit
+    #[serde(default)]
+    pub truncated: bool,
+}
+
+/// Output format for the extracted page content
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ContentFormat {
+    /// Plain text paragraphs
+    #[default]
+    Text,
+    /// Markdown keeping headings, lists, tables, code blocks and links
+    Markdown,
+}
+
+/// Errors returned when a page cannot be fetched or read
+#[derive(Debug, thiserror::Error)]
+pub enum FetchError {
+    #[error("Access to {0} is prohibited by robots.txt")]
+    RobotsDisallowed(String),
+    #[error("{url} returned HTTP {status}")]
+    HttpStatus { url: String, status: StatusCode },
+    #[error("{url} has unsupported content type \"{content_type}\"; only HTML, plain text and PDF documents can be read")]
+    UnsupportedContentType { url: String, content_type: String },
+    #[error("{url} is too large ({}the limit is {limit} bytes)", .size.map(|size| format!("{} bytes, ", size)).unwrap_or_default())]
+    BodyTooLarge {
+        url: String,
+        /// Size of the whole body, if known
+        size: Option<usize>,
+        limit: usize,
+    },
+    #[error("The publisher of {url} opted out of automated use ({directives})")]
+    OptedOut { url: String, directives: String },
+    #[error(transparent)]
+    UrlBlocked(#[from] UrlBlocked),
+    #[error(transparent)]
+    DomainBlocked(#[from] DomainBlocked),
+    #[error(transparent)]
+    Request(anyhow::Error),
+}
+
+/// Keeps fetch errors and URL and domain policy violations distinct when they
+/// surface from an `HttpClient`
+impl From<anyhow::Error> for FetchError {
+    fn from(error: anyhow::Error) -> Self {
+        let error = match error.downcast::<FetchError>() {
+            Ok(fetch_error) => return fetch_error,
+            Err(error) => error,
+        };
+        let error = match error.downcast::<UrlBlocked>() {
+            Ok(blocked) => return Self::UrlBlocked(blocked),
+            Err(error) => error,
+        };
+        match error.downcast::<DomainBlocked>() {
+            Ok(blocked) => Self::DomainBlocked(blocked),
+            Err(error) => Self::Request(error),
+        }
+    }
 }
 
 /// User-Agent string used for all HTTP requests
 pub(crate) const USER_AGENT: &str = "cipherant/0.1.0";
 
+/// Maximum number of redirects followed for a request
+pub(crate) const MAX_REDIRECTS: usize = 10;
+
+/// Default maximum size of a page body that will be parsed; longer pages are cut
+pub(crate) const MAX_BODY_BYTES: usize = 5 * 1024 * 1024;
+
+/// Default maximum size of a PDF document that will be parsed (a cut PDF can't be read)
+pub(crate) const MAX_PDF_BYTES: usize = 20 * 1024 * 1024;
+
+/// Largest documents the web tools read, from the `[limits]` table of the config file
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SizeLimits {
+    /// Pages (HTML, plain text) with a longer body are cut and flagged truncated
+    pub max_page_bytes: usize,
+    /// PDF documents with a larger body are refused
+    pub max_pdf_bytes: usize,
+}
+
+impl Default for SizeLimits {
+    fn default() -> Self {
+        Self {
+            max_page_bytes: MAX_BODY_BYTES,
+            max_pdf_bytes: MAX_PDF_BYTES,
+        }
+    }
+}
+
+impl SizeLimits {
+    /// Number of body bytes read from a response: `max_pdf_bytes` for PDFs,
+    /// `max_page_bytes` for pages and anything else
+    pub(crate) fn for_response(&self, response: &HttpResponse) -> usize {
+        if is_pdf(response) {
+            self.max_pdf_bytes
+        } else {
+            self.max_page_bytes
+        }
+    }
+}
+
+/// Response returned by an `HttpClient`, whatever its status
+#[derive(Debug, Clone)]
+pub(crate) struct HttpResponse {
+    /// HTTP status code
+    pub status: StatusCode,
+    /// Final URL after following redirects
+    pub url: String,
+    /// Response headers
+    pub headers: HeaderMap,
+    /// Raw response body
+    pub body: Vec<u8>,
+    /// Whether the body was cut at the client's size limit
+    pub truncated: bool,
+}
+
+impl HttpResponse {
+    /// Media type from the Content-Type header, lowercased and without parameters
+    /// (e.g. "text/html" for "text/html; charset=utf-8")
+    pub(crate) fn media_type(&self) -> Option<String> {
+        let content_type = self.headers.get(CONTENT_TYPE)?.to_str().ok()?;
+        let media_type = content_type.split(';').next()?.trim().to_lowercase();
+        (!media_type.is_empty()).then_some(media_type)
+    }
+
+    /// `charset` parameter of the Content-Type header, if any
+    pub(crate) fn charset(&self) -> Option<&str> {
+        let content_type = self.headers.get(CONTENT_TYPE)?.to_str().ok()?;
+        charset_param(content_type)
+    }
+}
+
+#[cfg(test)]
+impl HttpResponse {
+    /// Build a response for mock clients
+    pub(crate) fn new(url: &str, status: u16, content_type: &str, body: &[u8]) -> Self {
+        let mut headers = HeaderMap::new();
+        headers.insert(CONTENT_TYPE, content_type.parse().unwrap());
+        Self {
+            status: StatusCode::from_u16(status).unwrap(),
+            url: url.to_string(),
+            headers,
+            body: body.to_vec(),
+            truncated: false,
+        }
+    }
+
+    /// Body decoded as UTF-8, replacing invalid sequences
+    pub(crate) fn text(&self) -> String {
+        String::from_utf8_lossy(&self.body).into_owned()
+    }
+}
+
 /// Trait for HTTP client abstraction (enables mocking in tests)
 pub(crate) trait HttpClient {
-    async fn get(&self, url: &str) -> Result<String>;
+    /// Send a GET request. Non-2xx responses are returned as-is, not as errors.
+    async fn get(&self, url: &str) -> Result<HttpResponse>;
+
+    /// Send a GET request without following redirects, returning 3xx responses as-is.
+    /// Clients that never follow redirects can rely on the default.
+    async fn get_no_redirect(&self, url: &str) -> Result<HttpResponse> {
+        self.get(url).await
+    }
+
+    /// Like [`get_no_redirect`](Self::get_no_redirect), reading at most `max_body_bytes`
+    /// of the body: the rest is dropped and the response marked truncated.
+    /// Clients that don't limit bodies (test mocks) can rely on the default.
+    async fn get_no_redirect_limited(
+        &self,
+        url: &str,
+        _max_body_bytes: usize,
+    ) -> Result<HttpResponse> {
+        self.get_no_redirect(url).await
+    }
+
+    /// Send a GET request with extra headers, e.g. conditional request validators.
+    /// `max_body_bytes` overrides the client's size limits, e.g. for robots.txt.
+    /// Clients that can't send headers or limit bodies (test mocks) may ignore them.
+    async fn get_with_headers(
+        &self,
+        url: &str,
+        _headers: &HeaderMap,
+        follow_redirects: bool,
+        _max_body_bytes: Option<usize>,
+    ) -> Result<HttpResponse> {
+        if follow_redirects {
+            self.get(url).await
+        } else {
+            self.get_no_redirect(url).await
+        }
+    }
+
+    /// Maximum number of redirects followed for one request
+    fn max_redirects(&self) -> usize {
+        MAX_REDIRECTS
+    }
+
+    /// Resolve a host name to the addresses a request would connect to
+    async fn resolve(&self, host: &str, port: u16) -> Result<Vec<IpAddr>> {
+        Ok(tokio::net::lookup_host((host, port))
+            .await?
+            .map(|addr| addr.ip())
+            .collect())
+    }
 }
 
-pub(crate) struct ReqwestClient;
+/// Settings for the HTTP client shared by all web tools
+#[derive(Debug, Clone)]
+pub struct HttpConfig {
+    /// User-Agent header sent with every request
+    pub user_agent: String,
+    /// Timeout for a whole request, from connecting until the body is read
+    pub timeout: Duration,
+    /// Timeout for establishing a connection
+    pub connect_timeout: Duration,
+    /// Maximum number of redirects to follow
+    pub max_redirects: usize,
+    /// Proxy URL for all requests (e.g. "http://proxy.local:8080", "socks5://...")
+    pub proxy: Option<String>,
+    /// Accept gzip-compressed responses
+    pub gzip: bool,
+    /// Accept brotli-compressed responses
+    pub brotli: bool,
+    /// Maximum number of (decompressed) body bytes read from a page or a PDF; the rest
+    /// is dropped and the response marked truncated. Responses other than pages
+    /// declaring a larger Content-Length are rejected without reading the body.
+    pub size_limits: SizeLimits,
+    /// Hosts the client may connect to: host names resolving to a private or
+    /// reserved address are refused when connecting, unless the policy allows them.
+    /// `None` connects anywhere, e.g. for a search backend the user configured.
+    pub url_policy: Option<UrlPolicy>,
+}
+
+impl Default for HttpConfig {
+    fn default() -> Self {
+        Self {
+            user_agent: USER_AGENT.to_string(),
+            timeout: Duration::from_secs(30),
+            connect_timeout: Duration::from_secs(10),
+            max_redirects: MAX_REDIRECTS,
+            proxy: None,
+            gzip: true,
+            brotli: true,
+            size_limits: SizeLimits::default(),
+            url_policy: Some(UrlPolicy::default()),
+        }
+    }
+}
+
+/// `HttpClient` backed by a single `reqwest::Client`.
+/// Cloning is cheap and shares the connection pool, so build it once and hand clones
+/// to every tool.
+#[derive(Debug, Clone)]
+pub struct ReqwestClient {
+    client: reqwest::Client,
+    /// Same settings, but redirects are handed back to the caller
+    no_redirect_client: reqwest::Client,
+    max_redirects: usize,
+    size_limits: SizeLimits,
+}
+
+impl ReqwestClient {
+    /// Build a client from the given settings.
+    ///
+    /// # Errors
+    /// Returns an error if the proxy URL is invalid or the TLS backend cannot be initialized.
+    pub fn new(config: &HttpConfig) -> Result<Self> {
+        Ok(Self {
+            client: Self::builder(config)?
+                .redirect(Policy::limited(config.max_redirects))
+                .build()?,
+            no_redirect_client: Self::builder(config)?.redirect(Policy::none()).build()?,
+            max_redirects: config.max_redirects,
+            size_limits: config.size_limits,
+        })
+    }
+
+    fn builder(config: &HttpConfig) -> Result<reqwest::ClientBuilder> {
+        let mut builder = reqwest::Client::builder()
+            .user_agent(&config.user_agent)
+            .timeout(config.timeout)
+            .connect_timeout(config.connect_timeout)
+            .gzip(config.gzip)
+            .brotli(config.brotli);
+        if let Some(proxy) = &config.proxy {
+            builder = builder.proxy(Proxy::all(proxy)?);
+        }
+        if let Some(policy) = &config.url_policy {
+            builder = builder.dns_resolver(Arc::new(PolicyResolver::new(policy.clone())));
+        }
+        Ok(builder)
+    }
+
+    /// The underlying client, for requests other than plain GETs
+    pub(crate) fn inner(&self) -> &reqwest::Client {
+        &self.client
+    }
+}
 
 impl HttpClient for ReqwestClient {
-    async fn get(&self, url: &str) -> Result<String> {
-        let client = reqwest::Client::builder().user_agent(USER_AGENT).build()?;
-        let response = client.get(url).send().await?;
-        let text = response.text().await?;
+    async fn get(&self, url: &str) -> Result<HttpResponse> {
+        self.get_with_headers(url, &HeaderMap::new(), true, None)
+            .await
+    }
+
+    async fn get_no_redirect(&self, url: &str) -> Result<HttpResponse> {
+        self.get_with_headers(url, &HeaderMap::new(), false, None)
+            .await
+    }
+
+    async fn get_no_redirect_limited(
+        &self,
+        url: &str,
+        max_body_bytes: usize,
+    ) -> Result<HttpResponse> {
+        self.get_with_headers(url, &HeaderMap::new(), false, Some(max_body_bytes))
+            .await
+    }
+
+    async fn get_with_headers(
+        &self,
+        url: &str,
+        headers: &HeaderMap,
+        follow_redirects: bool,
+        max_body_bytes: Option<usize>,
+    ) -> Result<HttpResponse> {
+        let client = if follow_redirects {
+            &self.client
+        } else {
+            &self.no_redirect_client
+        };
+        let response = client.get(url).headers(headers.clone()).send().await?;
+        read_response(response, |head| {
+            max_body_bytes.unwrap_or_else(|| self.size_limits.for_response(head))
+        })
+        .await
+    }
+
+    fn max_redirects(&self) -> usize {
+        self.max_redirects
+    }
+}
+
+/// Read the body chunk by chunk, stopping at the limit `max_body_bytes` picks from
+/// the status and headers. Pages (HTML, plain text) declaring a larger Content-Length
+/// are read up to the limit and marked truncated; any other body is rejected
+/// without being read.
+pub(crate) async fn read_response(
+    mut response: reqwest::Response,
+    max_body_bytes: impl FnOnce(&HttpResponse) -> usize,
+) -> Result<HttpResponse> {
+    let mut read = HttpResponse {
+        status: response.status(),
+        url: response.url().to_string(),
+        headers: response.headers().clone(),
+        body: Vec::new(),
+        truncated: false,
+    };
+    let max_body_bytes = max_body_bytes(&read);
+    // Unknown when the body is compressed, since it is decompressed on the fly
+    if let Some(size) = response.content_length() {
+        if size > max_body_bytes as u64 && !is_page(&read) {
+            return Err(FetchError::BodyTooLarge {
+                url: read.url,
+                size: Some(size as usize),
+                limit: max_body_bytes,
+            }
+            .into());
+        }
+    }
+
+    while let Some(chunk) = response.chunk().await? {
+        let room = max_body_bytes - read.body.len();
+        if chunk.len() > room {
+            read.body.extend_from_slice(&chunk[..room]);
+            read.truncated = true;
+            break;
+        }
+        read.body.extend_from_slice(&chunk);
+    }
+    Ok(read)
+}
+
+/// Shared state for fetching pages: the HTTP client, the URL and domain policies, the
+/// robots.txt cache, the per-host politeness scheduler, the retry policy, the
+/// document size limits and the optional on-disk HTTP cache.
+/// Tools reading web pages share one context through an `Arc`.
+pub struct FetchContext<C = ReqwestClient> {
+    pub(crate) client: C,
+    pub(crate) robots_cache: RobotsCache,
+    scheduler: HostScheduler,
+    http_cache: Option<HttpCache>,
+    opt_out_policy: OptOutPolicy,
+    url_policy: UrlPolicy,
+    pub(crate) domain_policy: DomainPolicy,
+    retry_policy: RetryPolicy,
+    size_limits: SizeLimits,
+}
+
+impl<C> FetchContext<C> {
+    /// Create a context fetching through `client`, spacing out requests per host
+    /// according to `politeness`
+    pub fn new(client: C, politeness: PolitenessConfig) -> Self {
+        Self {
+            client,
+            robots_cache: RobotsCache::new(),
+            scheduler: HostScheduler::new(politeness),
+            http_cache: None,
+            opt_out_policy: OptOutPolicy::default(),
+            url_policy: UrlPolicy::default(),
+            domain_policy: DomainPolicy::default(),
+            retry_policy: RetryPolicy::default(),
+            size_limits: SizeLimits::default(),
+        }
+    }
+
+    /// Serve and store responses (pages and robots.txt) through an on-disk cache
+    pub fn with_http_cache(mut self, http_cache: HttpCache) -> Self {
+        self.http_cache = Some(http_cache);
+        self
+    }
+
+    /// Set what to do with pages whose publisher opted out via `X-Robots-Tag`
+    /// or `<meta name="robots">` (enforced by default)
+    pub fn with_opt_out_policy(mut self, policy: OptOutPolicy) -> Self {
+        self.opt_out_policy = policy;
+        self
+    }
+
+    /// Set which URLs may be fetched (by default only http(s) URLs of public hosts)
+    pub fn with_url_policy(mut self, policy: UrlPolicy) -> Self {
+        self.url_policy = policy;
+        self
+    }
 
-        Ok(text)
+    /// Keep page fetches (and the redirects they follow) to the sites `policy` allows.
+    /// robots.txt files are still read from any site.
+    pub fn with_domain_policy(mut self, policy: DomainPolicy) -> Self {
+        self.domain_policy = policy;
+        self
+    }
+
+    /// Set how requests failing with a transient error are retried
+    pub fn with_retry_policy(mut self, policy: RetryPolicy) -> Self {
+        self.retry_policy = policy;
+        self
+    }
+
+    /// Set the largest pages and PDFs that are read. The client should read no more
+    /// (see [`HttpConfig::size_limits`]), or bodies are downloaded only to be cut.
+    pub fn with_size_limits(mut self, limits: SizeLimits) -> Self {
+        self.size_limits = limits;
+        self
+    }
+}
+
+/// Check the final URL of a cached response against the current URL and domain
+/// policies, which may have changed since it was stored
+async fn check_cached<C: HttpClient>(context: &FetchContext<C>, final_url: &str) -> Result<()> {
+    context.domain_policy.check(final_url)?;
+    context.url_policy.check(&context.client, final_url).await
+}
+
+/// GET through the context's on-disk cache: fresh responses are served without
+/// a request, stale ones are revalidated and new ones stored
+async fn get_cached<C: HttpClient>(
+    context: &FetchContext<C>,
+    url: &str,
+    follow_redirects: bool,
+    max_body_bytes: Option<usize>,
+) -> Result<HttpResponse> {
+    let Some(http_cache) = &context.http_cache else {
+        return context
+            .get_with_headers(url, &HeaderMap::new(), follow_redirects, max_body_bytes)
+            .await;
+    };
+
+    let stale = match http_cache.lookup(url, follow_redirects).await {
+        CacheLookup::Fresh(response) => {
+            check_cached(context, &response.url).await?;
+            info!("Cache hit: {}", url);
+            return Ok(response);
+        }
+        CacheLookup::Stale(response) => {
+            check_cached(context, &response.url).await?;
+            Some(response)
+        }
+        CacheLookup::Miss => None,
+    };
+    let headers = stale.as_ref().map(validators).unwrap_or_default();
+    let response = context
+        .get_with_headers(url, &headers, follow_redirects, max_body_bytes)
+        .await?;
+
+    match stale {
+        Some(stale) if response.status == StatusCode::NOT_MODIFIED => {
+            info!("Cache hit (revalidated): {}", url);
+            Ok(http_cache
+                .revalidated(url, follow_redirects, stale, &response)
+                .await)
+        }
+        _ => {
+            http_cache.store(url, follow_redirects, &response).await;
+            Ok(response)
+        }
     }
 }
 
-pub(crate) async fn fetch_url(url: &str, robots_cache: &RobotsCache) -> Result<PageContent> {
-    let request_client = ReqwestClient {};
-    let page_content = fetch_url_with_client(&request_client, robots_cache, url).await?;
+/// Requests made through the context (page and robots.txt fetches alike) go through
+/// the on-disk cache, then wait for the politeness scheduler, honouring the origin's
+/// `Crawl-delay`. Redirects are followed here, so that the URL policy, robots.txt and
+/// the scheduler see every hop, and each request is retried after transient failures.
+impl<C: HttpClient> HttpClient for FetchContext<C> {
+    async fn get(&self, url: &str) -> Result<HttpResponse> {
+        get_cached(self, url, true, None).await
+    }
+
+    async fn get_no_redirect(&self, url: &str) -> Result<HttpResponse> {
+        get_cached(self, url, false, None).await
+    }
+
+    async fn get_no_redirect_limited(
+        &self,
+        url: &str,
+        max_body_bytes: usize,
+    ) -> Result<HttpResponse> {
+        get_cached(self, url, false, Some(max_body_bytes)).await
+    }
+
+    /// Bypasses the on-disk cache
+    async fn get_with_headers(
+        &self,
+        url: &str,
+        headers: &HeaderMap,
+        follow_redirects: bool,
+        max_body_bytes: Option<usize>,
+    ) -> Result<HttpResponse> {
+        let mut url = url.to_string();
+        self.url_policy.check(&self.client, &url).await?;
+        for _ in 0..=self.client.max_redirects() {
+            let hop = url.as_str();
+            let response = self
+                .retry_policy
+                .run(hop, || async move {
+                    let crawl_delay = self.robots_cache.crawl_delay(hop);
+                    let _permit = self.scheduler.acquire(hop, crawl_delay).await;
+                    self.client
+                        .get_with_headers(hop, headers, false, max_body_bytes)
+                        .await
+                })
+                .await?;
+
+            let target = follow_redirects
+                .then(|| redirect_target(&url, &response))
+                .flatten();
+            match target {
+                Some(target) => {
+                    self.domain_policy.check(&target)?;
+                    // Before robots.txt, which would otherwise be requested from a blocked host
+                    self.url_policy.check(&self.client, &target).await?;
+                    // Boxed: robots.txt is itself fetched through this context
+                    if !Box::pin(self.robots_cache.is_allowed(self, &target)).await {
+                        return Err(FetchError::RobotsDisallowed(target).into());
+                    }
+                    url = target;
+                }
+                None => return Ok(response),
+            }
+        }
+        anyhow::bail!("Too many redirects, last to {}", url)
+    }
+
+    fn max_redirects(&self) -> usize {
+        self.client.max_redirects()
+    }
 
-    Ok(page_content)
+    async fn resolve(&self, host: &str, port: u16) -> Result<Vec<IpAddr>> {
+        self.client.resolve(host, port).await
+    }
+}
+
+/// Absolute URL a redirect response points to, if it is a redirect with a usable `Location`
+pub(crate) fn redirect_target(url: &str, response: &HttpResponse) -> Option<String> {
+    if !matches!(response.status.as_u16(), 301 | 302 | 303 | 307 | 308) {
+        return None;
+    }
+    let location = response.headers.get(LOCATION)?.to_str().ok()?;
+    Url::parse(url)
+        .ok()?
+        .join(location)
+        .ok()
+        .map(|u| u.to_string())
 }
 
-/// Fetch URL content using the provided HTTP client
-async fn fetch_url_with_client<C: HttpClient>(
-    client: &C,
-    robots_cache: &RobotsCache,
+/// Fetch a page (after checking the URL policy and robots.txt) and extract its content
+pub(crate) async fn fetch_url<C: HttpClient>(
+    context: &FetchContext<C>,
     url: &str,
-) -> Result<PageContent> {
-    if !robots_cache.is_allowed(client, url).await {
-        bail!("Access to {} is prohibited by robots.txt", url);
+    format: ContentFormat,
+) -> Result<PageContent, FetchError> {
+    context.domain_policy.check(url)?;
+    // Before robots.txt, which would otherwise be requested from a blocked host
+    context.url_policy.check(&context.client, url).await?;
+    if !context.robots_cache.is_allowed(context, url).await {
+        return Err(FetchError::RobotsDisallowed(url.to_string()));
     }
 
-    let html = client.get(url).await?;
-    Ok(parse_html(url, &html))
+    let mut response = context.get(url).await?;
+    if !response.status.is_success() {
+        return Err(FetchError::HttpStatus {
+            url: response.url,
+            status: response.status,
+        });
+    }
+    check_opt_out(
+        context.opt_out_policy,
+        &response.url,
+        &header_directives(&response.headers),
+    )?;
+    let limits = context.size_limits;
+    if is_pdf(&response) {
+        if response.truncated {
+            // Only the declared size is known, if any; the body was cut at the
+            // client's own limit when it is the lower one
+            return Err(FetchError::BodyTooLarge {
+                size: response
+                    .headers
+                    .get(CONTENT_LENGTH)
+                    .and_then(|length| length.to_str().ok()?.parse().ok()),
+                limit: limits.max_pdf_bytes.min(response.body.len()),
+                url: response.url,
+            });
+        }
+        if response.body.len() > limits.max_pdf_bytes {
+            return Err(FetchError::BodyTooLarge {
+                url: response.url,
+                size: Some(response.body.len()),
+                limit: limits.max_pdf_bytes,
+            });
+        }
+        return read_pdf_response(response).await;
+    }
+    if response.body.len() > limits.max_page_bytes {
+        response.body.truncate(limits.max_page_bytes);
+        response.truncated = true;
+    }
+    if response.truncated {
+        info!("{} was cut at {} bytes", response.url, response.body.len());
+    }
+
+    match response.media_type().as_deref() {
+        // Servers that omit Content-Type mostly serve HTML
+        None | Some("text/html") | Some("application/xhtml+xml") => {
+            let html = decode_html(&response.body, response.charset());
+            let document = Html::parse_document(&html);
+            check_opt_out(
+                context.opt_out_policy,
+                &response.url,
+                &meta_directives(&document),
+            )?;
+            Ok(PageContent {
+                truncated: response.truncated,
+                ..parse_document(&response.url, &document, format)
+            })
+        }
+        Some("text/plain") => Ok(PageContent {
+            url: response.url.clone(),
+            title: None,
+            text: decode_text(&response.body, response.charset()),
+            pages: Vec::new(),
+            metadata: PageMetadata::default(),
+            links: Vec::new(),
+            truncated: response.truncated,
+        }),
+        Some(other) => Err(FetchError::UnsupportedContentType {
+            url: response.url.clone(),
+            content_type: other.to_string(),
+        }),
+    }
+}
+
+/// A response is a page (HTML or plain text), which is still readable when cut,
+/// when its content type says so or it has none and isn't a PDF
+fn is_page(response: &HttpResponse) -> bool {
+    match response.media_type().as_deref() {
+        None => !is_pdf(response),
+        Some("text/html") | Some("application/xhtml+xml") | Some("text/plain") => true,
+        _ => false,
+    }
+}
+
+/// A response is a PDF when its content type says so, or when a `.pdf` URL
+/// comes back without a more specific content type
+fn is_pdf(response: &HttpResponse) -> bool {
+    match response.media_type().as_deref() {
+        Some("application/pdf") | Some("application/x-pdf") => true,
+        None | Some("application/octet-stream") | Some("binary/octet-stream") => {
+            Url::parse(&response.url)
+                .map(|u| u.path().to_lowercase().ends_with(".pdf"))
+                .unwrap_or(false)
+        }
+        _ => false,
+    }
 }
 
-fn parse_html(url: &str, html: &str) -> PageContent {
-    let document = Html::parse_document(html);
+/// Extract the title and text of a downloaded PDF, with page markers (parsing is
+/// CPU-bound, so it runs on the blocking thread pool)
+async fn read_pdf_response(response: HttpResponse) -> Result<PageContent, FetchError> {
+    let HttpResponse { url, body, .. } = response;
+    let pdf = tokio::task::spawn_blocking(move || parse_pdf(&url, &body))
+        .await
+        .map_err(anyhow::Error::from)??;
 
+    Ok(PageContent {
+        text: format_pages(&number_pages(&pdf.pages)),
+        pages: pdf.pages,
+        url: pdf.path,
+        title: pdf.title,
+        metadata: PageMetadata::default(),
+        links: Vec::new(),
+        truncated: false,
+    })
+}
+
+/// Fail with `FetchError::OptedOut` if `directives` opt out of automated use
+/// and the policy is enforced
+fn check_opt_out(policy: OptOutPolicy, url: &str, directives: &[String]) -> Result<(), FetchError> {
+    if policy == OptOutPolicy::Ignore {
+        return Ok(());
+    }
+    let opt_outs = opt_outs(directives);
+    if opt_outs.is_empty() {
+        return Ok(());
+    }
+    let directives = opt_outs.join(", ");
+    if policy == OptOutPolicy::Warn {
+        warn!(
+            "The publisher of {} opted out of automated use ({})",
+            url, directives
+        );
+        return Ok(());
+    }
+    Err(FetchError::OptedOut {
+        url: url.to_string(),
+        directives,
+    })
+}
+
+#[cfg(test)]
+fn parse_html(url: &str, html: &str, format: ContentFormat) -> PageContent {
+    parse_document(url, &Html::parse_document(html), format)
+}
+
+fn parse_document(url: &str, document: &Html, format: ContentFormat) -> PageContent {
     // Extract title
     let title_selector = Selector::parse("title").unwrap();
     let title = document
@@ -67,24 +805,30 @@ fn parse_html(url: &str, html: &str) -> PageContent {
         .next()
         .map(|element| element.text().collect::<String>());
 
-    // Extract <p> tag (body text)
-    let p_selector = Selector::parse("p").unwrap();
-    let body = document
-        .select(&p_selector)
-        .map(|element| element.text().collect())
-        .collect::<Vec<String>>()
-        .join("\n\n");
+    // Extract the main content, leaving out navigation, footers, ads, etc.
+    let main_content = extract_main_content(document);
+    let body = match format {
+        ContentFormat::Text => to_text(&main_content),
+        ContentFormat::Markdown => to_markdown(&main_content, document_base_url(document, url)),
+    };
 
     PageContent {
         url: url.to_string(),
         title,
         text: body,
+        pages: Vec::new(),
+        metadata: extract_metadata(document, url),
+        links: extract_links(document, url),
+        truncated: false,
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::collectors::http_cache::CacheMode;
+    use wiremock::matchers::{header, method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
 
     #[test]
     fn test_parse_html_extracts_title() {
@@ -97,7 +841,7 @@ mod tests {
         "#;
 
         // Act
-        let result = parse_html("https://example.com", html);
+        let result = parse_html("https://example.com", html, ContentFormat::Text);
 
         // Assert
         assert_eq!(result.title, Some("Test Page".to_string()));
@@ -116,7 +860,7 @@ mod tests {
             </html>
         "#;
 
-        let result = parse_html("https://example.com", html);
+        let result = parse_html("https://example.com", html, ContentFormat::Text);
 
         assert_eq!(result.text, "First paragraph\n\nSecond paragraph");
     }
@@ -130,14 +874,142 @@ mod tests {
             </html>
         "#;
 
-        let result = parse_html("https://example.com", html);
+        let result = parse_html("https://example.com", html, ContentFormat::Text);
 
         assert_eq!(result.title, None);
     }
 
+    #[test]
+    fn test_parse_html_extracts_content_outside_paragraphs() {
+        // Content in <li>, <pre> and <h2> should not be lost
+        let html = r#"
+            <html>
+                <body>
+                    <nav><ul><li><a href="/">Home</a></li></ul></nav>
+                    <h2>Steps</h2>
+                    <ul><li>Install the toolchain</li><li>Run the build</li></ul>
+                    <pre>cargo build</pre>
+                    <footer><p>Copyright</p></footer>
+                </body>
+            </html>
+        "#;
+
+        let result = parse_html("https://example.com", html, ContentFormat::Text);
+
+        assert_eq!(
+            result.text,
+            "Steps\n\nInstall the toolchain\n\nRun the build\n\ncargo build"
+        );
+    }
+
+    #[test]
+    fn test_parse_html_extracts_metadata() {
+        let html = r#"
+            <html lang="en">
+                <head>
+                    <title>Story</title>
+                    <meta name="author" content="Jane Reporter">
+                    <meta property="article:published_time" content="2025-01-02T03:04:05Z">
+                </head>
+                <body><p>Body</p></body>
+            </html>
+        "#;
+
+        let result = parse_html("https://example.com/story", html, ContentFormat::Text);
+
+        assert_eq!(result.metadata.author, Some("Jane Reporter".to_string()));
+        assert_eq!(
+            result.metadata.published_time,
+            Some("2025-01-02T03:04:05Z".to_string())
+        );
+        assert_eq!(result.metadata.language, Some("en".to_string()));
+    }
+
+    #[test]
+    fn test_parse_html_extracts_links() {
+        let html = r#"
+            <html>
+                <body>
+                    <nav><a href="/docs/">Docs</a></nav>
+                    <p>See <a href="intro">the intro</a>.</p>
+                </body>
+            </html>
+        "#;
+
+        let result = parse_html("https://example.com/guide/", html, ContentFormat::Text);
+
+        assert_eq!(
+            result.links,
+            vec![
+                Link {
+                    url: "https://example.com/docs/".to_string(),
+                    text: "Docs".to_string(),
+                },
+                Link {
+                    url: "https://example.com/guide/intro".to_string(),
+                    text: "the intro".to_string(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_html_as_markdown() {
+        let html = r#"
+            <html>
+                <body>
+                    <h1>Guide</h1>
+                    <p>Read the <a href="/docs">docs</a>.</p>
+                    <ul><li>One</li><li>Two</li></ul>
+                </body>
+            </html>
+        "#;
+
+        let result = parse_html("https://example.com/guide", html, ContentFormat::Markdown);
+
+        assert_eq!(
+            result.text,
+            "# Guide\n\nRead the [docs](https://example.com/docs).\n\n- One\n- Two"
+        );
+    }
+
+    #[test]
+    fn test_parse_html_as_markdown_resolves_against_base_href() {
+        let html = r#"
+            <html>
+                <head><base href="https://cdn.example.com/v2/"></head>
+                <body>
+                    <p>Read the <a href="guide">guide</a>.</p>
+                    <p><img src="diagram.png" alt="Diagram"></p>
+                </body>
+            </html>
+        "#;
+
+        let result = parse_html("https://example.com/docs/", html, ContentFormat::Markdown);
+
+        assert_eq!(
+            result.text,
+            "Read the [guide](https://cdn.example.com/v2/guide).\n\n![Diagram](https://cdn.example.com/v2/diagram.png)"
+        );
+        assert_eq!(result.links[0].url, "https://cdn.example.com/v2/guide");
+    }
+
+    /// Fetch context without request spacing, so tests don't wait, that may
+    /// fetch from local stand-in servers
+    fn context<C>(client: C) -> FetchContext<C> {
+        FetchContext::new(
+            client,
+            PolitenessConfig {
+                min_interval: Duration::ZERO,
+                ..PolitenessConfig::default()
+            },
+        )
+        .with_url_policy(UrlPolicy::default().allow_host("127.0.0.1"))
+    }
+
     /// Mock HTTP client for testing (supports URL-specific responses)
     struct MockHttpClient {
-        responses: std::collections::HashMap<String, String>,
+        responses: std::collections::HashMap<String, HttpResponse>,
     }
 
     impl MockHttpClient {
@@ -147,18 +1019,29 @@ mod tests {
             }
         }
 
-        fn with_response(mut self, url: &str, body: &str) -> Self {
-            self.responses.insert(url.to_string(), body.to_string());
+        fn with_response(self, url: &str, body: &str) -> Self {
+            self.with_http_response(HttpResponse::new(url, 200, "text/html", body.as_bytes()))
+        }
+
+        fn with_http_response(mut self, response: HttpResponse) -> Self {
+            self.responses.insert(response.url.clone(), response);
             self
         }
     }
 
     impl HttpClient for MockHttpClient {
-        async fn get(&self, url: &str) -> Result<String> {
-            self.responses
+        /// Unknown URLs get a 404, like on a real server
+        async fn get(&self, url: &str) -> Result<HttpResponse> {
+            Ok(self
+                .responses
                 .get(url)
                 .cloned()
-                .ok_or_else(|| anyhow::anyhow!("No mock response for {}", url))
+                .unwrap_or_else(|| HttpResponse::new(url, 404, "text/html", b"")))
+        }
+
+        /// Every host is public
+        async fn resolve(&self, _host: &str, _port: u16) -> Result<Vec<IpAddr>> {
+            Ok(vec!["93.184.215.14".parse().unwrap()])
         }
     }
 
@@ -169,14 +1052,18 @@ mod tests {
         let mock_client = MockHttpClient::new()
             .with_response("https://example.com/robots.txt", "User-agent: *\nAllow: /")
             .with_response("https://example.com", mock_html);
-        let robots_cache = RobotsCache::new();
 
-        let result = fetch_url_with_client(&mock_client, &robots_cache, "https://example.com")
-            .await
-            .unwrap();
+        let result = fetch_url(
+            &context(mock_client),
+            "https://example.com",
+            ContentFormat::Text,
+        )
+        .await
+        .unwrap();
 
         assert_eq!(result.title, Some("Mock Page".to_string()));
         assert_eq!(result.text, "Mock content");
+        assert!(!result.truncated);
     }
 
     #[tokio::test]
@@ -185,12 +1072,11 @@ mod tests {
             "https://example.com/robots.txt",
             "User-agent: *\nDisallow: /private",
         );
-        let robots_cache = RobotsCache::new();
 
-        let result = fetch_url_with_client(
-            &mock_client,
-            &robots_cache,
+        let result = fetch_url(
+            &context(mock_client),
             "https://example.com/private/page",
+            ContentFormat::Text,
         )
         .await;
 
@@ -199,18 +1085,1138 @@ mod tests {
     }
 
     #[tokio::test]
-    async fn test_fetch_allowed_when_robots_txt_missing() {
-        // No robots.txt response → graceful fallback → fetch proceeds
-        let mock_client = MockHttpClient::new().with_response(
-            "https://example.com/page",
-            "<html><body><p>Content</p></body></html>",
+    async fn test_fetch_fails_on_http_error_status() {
+        let mock_client = MockHttpClient::new().with_http_response(HttpResponse::new(
+            "https://example.com/missing",
+            404,
+            "text/html",
+            b"<html><body><p>Sorry, this page could not be found anywhere.</p></body></html>",
+        ));
+
+        let result = fetch_url(
+            &context(mock_client),
+            "https://example.com/missing",
+            ContentFormat::Text,
+        )
+        .await;
+
+        let err = result.unwrap_err();
+        assert!(matches!(
+            err,
+            FetchError::HttpStatus { status, .. } if status == StatusCode::NOT_FOUND
+        ));
+        assert_eq!(
+            err.to_string(),
+            "https://example.com/missing returned HTTP 404 Not Found"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_fetch_rejects_unsupported_content_type() {
+        let mock_client = MockHttpClient::new().with_http_response(HttpResponse::new(
+            "https://example.com/api",
+            200,
+            "application/json; charset=utf-8",
+            br#"{"key": "value"}"#,
+        ));
+
+        let result = fetch_url(
+            &context(mock_client),
+            "https://example.com/api",
+            ContentFormat::Text,
+        )
+        .await;
+
+        assert!(matches!(
+            result.unwrap_err(),
+            FetchError::UnsupportedContentType { content_type, .. } if content_type == "application/json"
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_fetch_cuts_oversized_page() {
+        let body = vec![b'a'; MAX_BODY_BYTES + 1];
+        let mock_client = MockHttpClient::new().with_http_response(HttpResponse::new(
+            "https://example.com/huge.txt",
+            200,
+            "text/plain",
+            &body,
+        ));
+
+        let result = fetch_url(
+            &context(mock_client),
+            "https://example.com/huge.txt",
+            ContentFormat::Text,
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(result.text.len(), MAX_BODY_BYTES);
+        assert!(result.truncated);
+    }
+
+    #[tokio::test]
+    async fn test_fetch_flags_page_cut_by_client() {
+        let mut response = HttpResponse::new(
+            "https://example.com/long",
+            200,
+            "text/html",
+            b"<html><body><p>The beginning of a long story",
         );
-        let robots_cache = RobotsCache::new();
+        response.truncated = true;
+        let mock_client = MockHttpClient::new().with_http_response(response);
 
-        let result = fetch_url_with_client(&mock_client, &robots_cache, "https://example.com/page")
+        let result = fetch_url(
+            &context(mock_client),
+            "https://example.com/long",
+            ContentFormat::Text,
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(result.text, "The beginning of a long story");
+        assert!(result.truncated);
+    }
+
+    #[tokio::test]
+    async fn test_fetch_extracts_pdf_served_by_server() {
+        let pdf = std::fs::read("tests/fixtures/pdf/sample.pdf").unwrap();
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/papers/report"))
+            .respond_with(ResponseTemplate::new(200).set_body_raw(pdf, "application/pdf"))
+            .mount(&server)
+            .await;
+        let client = ReqwestClient::new(&HttpConfig::default()).unwrap();
+        let url = format!("{}/papers/report", server.uri());
+
+        let result = fetch_url(&context(client), &url, ContentFormat::Text)
             .await
             .unwrap();
 
+        assert_eq!(result.url, url);
+        assert_eq!(result.title, Some("Sample Report".to_string()));
+        assert!(result.text.contains("Hello from the sample report."));
+        assert!(result.pages.len() > 1);
+        assert!(result.pages[0].contains("Hello from the sample report."));
+    }
+
+    #[tokio::test]
+    async fn test_fetch_treats_pdf_extension_as_pdf_without_content_type() {
+        let pdf = std::fs::read("tests/fixtures/pdf/sample.pdf").unwrap();
+        let mock_client = MockHttpClient::new().with_http_response(HttpResponse::new(
+            "https://example.com/spec.PDF",
+            200,
+            "application/octet-stream",
+            &pdf,
+        ));
+
+        let result = fetch_url(
+            &context(mock_client),
+            "https://example.com/spec.PDF",
+            ContentFormat::Text,
+        )
+        .await
+        .unwrap();
+
+        assert!(result.text.contains("Chapter two starts on page two."));
+    }
+
+    #[tokio::test]
+    async fn test_fetch_rejects_oversized_pdf() {
+        let body = vec![b'a'; MAX_PDF_BYTES + 1];
+        let mock_client = MockHttpClient::new().with_http_response(HttpResponse::new(
+            "https://example.com/huge.pdf",
+            200,
+            "application/pdf",
+            &body,
+        ));
+
+        let result = fetch_url(
+            &context(mock_client),
+            "https://example.com/huge.pdf",
+            ContentFormat::Text,
+        )
+        .await;
+
+        assert!(matches!(
+            result.unwrap_err(),
+            FetchError::BodyTooLarge { size, limit, .. }
+                if size == Some(MAX_PDF_BYTES + 1) && limit == MAX_PDF_BYTES
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_fetch_applies_configured_size_limits() {
+        let mock_client = MockHttpClient::new()
+            .with_http_response(HttpResponse::new(
+                "https://example.com/long.txt",
+                200,
+                "text/plain",
+                &[b'a'; 20],
+            ))
+            .with_http_response(HttpResponse::new(
+                "https://example.com/big.pdf",
+                200,
+                "application/pdf",
+                &[b'a'; 200],
+            ));
+        let context = context(mock_client).with_size_limits(SizeLimits {
+            max_page_bytes: 10,
+            max_pdf_bytes: 100,
+        });
+
+        let page = fetch_url(
+            &context,
+            "https://example.com/long.txt",
+            ContentFormat::Text,
+        )
+        .await
+        .unwrap();
+        assert_eq!(page.text, "a".repeat(10));
+        assert!(page.truncated);
+
+        let result = fetch_url(&context, "https://example.com/big.pdf", ContentFormat::Text).await;
+        assert!(matches!(
+            result.unwrap_err(),
+            FetchError::BodyTooLarge {
+                size: Some(200),
+                limit: 100,
+                ..
+            }
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_fetch_reports_pdf_cut_by_client_without_claiming_its_size() {
+        let mut cut = HttpResponse::new(
+            "https://example.com/cut.pdf",
+            200,
+            "application/pdf",
+            &[b'a'; 50],
+        );
+        cut.truncated = true;
+        let mut declared = cut.clone();
+        declared.url = "https://example.com/declared.pdf".to_string();
+        declared
+            .headers
+            .insert(CONTENT_LENGTH, "5000".parse().unwrap());
+        let context = context(
+            MockHttpClient::new()
+                .with_http_response(cut)
+                .with_http_response(declared),
+        );
+
+        let err = fetch_url(&context, "https://example.com/cut.pdf", ContentFormat::Text)
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            FetchError::BodyTooLarge {
+                size: None,
+                limit: 50,
+                ..
+            }
+        ));
+        assert_eq!(
+            err.to_string(),
+            "https://example.com/cut.pdf is too large (the limit is 50 bytes)"
+        );
+
+        let result = fetch_url(
+            &context,
+            "https://example.com/declared.pdf",
+            ContentFormat::Text,
+        )
+        .await;
+        assert!(matches!(
+            result.unwrap_err(),
+            FetchError::BodyTooLarge {
+                size: Some(5000),
+                limit: 50,
+                ..
+            }
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_fetch_decodes_page_declaring_charset_in_meta() {
+        let body = include_bytes!("../../tests/fixtures/html/shift_jis.html");
+        let mock_client = MockHttpClient::new().with_http_response(HttpResponse::new(
+            "https://example.jp/",
+            200,
+            "text/html",
+            body,
+        ));
+
+        let result = fetch_url(
+            &context(mock_client),
+            "https://example.jp/",
+            ContentFormat::Text,
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(result.title, Some("日本語のページ".to_string()));
+        assert!(result.text.contains("これはシフトJISで書かれた文章です。"));
+    }
+
+    #[tokio::test]
+    async fn test_fetch_decodes_page_using_header_charset() {
+        let body = "<html><body><p>東京は晴れです</p></body></html>";
+        let (euc_jp, _, _) = encoding_rs::EUC_JP.encode(body);
+        let mock_client = MockHttpClient::new().with_http_response(HttpResponse::new(
+            "https://example.jp/weather",
+            200,
+            "text/html; charset=EUC-JP",
+            &euc_jp,
+        ));
+
+        let result = fetch_url(
+            &context(mock_client),
+            "https://example.jp/weather",
+            ContentFormat::Text,
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(result.text, "東京は晴れです");
+    }
+
+    #[tokio::test]
+    async fn test_fetch_returns_plain_text_as_is() {
+        let mock_client = MockHttpClient::new().with_http_response(HttpResponse::new(
+            "https://example.com/notes.txt",
+            200,
+            "text/plain",
+            b"line one\nline two",
+        ));
+
+        let result = fetch_url(
+            &context(mock_client),
+            "https://example.com/notes.txt",
+            ContentFormat::Text,
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(result.title, None);
+        assert_eq!(result.text, "line one\nline two");
+    }
+
+    #[tokio::test]
+    async fn test_fetch_refuses_page_opted_out_by_header() {
+        let mut response = HttpResponse::new(
+            "https://example.com/article",
+            200,
+            "text/html",
+            b"<html><body><p>Members only.</p></body></html>",
+        );
+        response
+            .headers
+            .insert("x-robots-tag", "noai, noimageai".parse().unwrap());
+        let mock_client = MockHttpClient::new().with_http_response(response);
+
+        let result = fetch_url(
+            &context(mock_client),
+            "https://example.com/article",
+            ContentFormat::Text,
+        )
+        .await;
+
+        let err = result.unwrap_err();
+        assert!(matches!(
+            &err,
+            FetchError::OptedOut { directives, .. } if directives == "noai"
+        ));
+        assert_eq!(
+            err.to_string(),
+            "The publisher of https://example.com/article opted out of automated use (noai)"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_fetch_refuses_page_opted_out_by_meta_robots() {
+        let mock_client = MockHttpClient::new().with_response(
+            "https://example.com/article",
+            r#"<html><head><meta name="robots" content="noindex"></head>
+               <body><p>Not for robots.</p></body></html>"#,
+        );
+
+        let result = fetch_url(
+            &context(mock_client),
+            "https://example.com/article",
+            ContentFormat::Text,
+        )
+        .await;
+
+        assert!(matches!(result.unwrap_err(), FetchError::OptedOut { .. }));
+    }
+
+    #[tokio::test]
+    async fn test_fetch_reads_opted_out_page_unless_enforced() {
+        let html = r#"<html><head><meta name="robots" content="noai"></head>
+                      <body><p>Not for robots.</p></body></html>"#;
+
+        for policy in [OptOutPolicy::Warn, OptOutPolicy::Ignore] {
+            let mock_client =
+                MockHttpClient::new().with_response("https://example.com/article", html);
+            let context = context(mock_client).with_opt_out_policy(policy);
+
+            let result = fetch_url(&context, "https://example.com/article", ContentFormat::Text)
+                .await
+                .unwrap();
+
+            assert_eq!(result.text, "Not for robots.");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_fetch_ignores_image_only_opt_out() {
+        let mock_client = MockHttpClient::new().with_response(
+            "https://example.com/gallery",
+            r#"<html><head><meta name="robots" content="noimageai"></head>
+               <body><p>Captions are fine.</p></body></html>"#,
+        );
+
+        let result = fetch_url(
+            &context(mock_client),
+            "https://example.com/gallery",
+            ContentFormat::Text,
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(result.text, "Captions are fine.");
+    }
+
+    // --- On-disk cache tests (against a local stand-in server) ---
+
+    fn cached_context(cache_dir: &std::path::Path) -> FetchContext {
+        let client = ReqwestClient::new(&HttpConfig::default()).unwrap();
+        context(client).with_http_cache(HttpCache::new(cache_dir, CacheMode::Normal))
+    }
+
+    async fn requests_to(server: &MockServer, page_path: &str) -> Vec<wiremock::Request> {
+        server
+            .received_requests()
+            .await
+            .unwrap()
+            .into_iter()
+            .filter(|r| r.url.path() == page_path)
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_fetch_serves_fresh_page_from_disk_cache_across_sessions() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/page"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .insert_header("cache-control", "max-age=600")
+                    .set_body_raw("<html><body><p>Cached page</p></body></html>", "text/html"),
+            )
+            .mount(&server)
+            .await;
+        let cache_dir = tempfile::tempdir().unwrap();
+        let url = format!("{}/page", server.uri());
+
+        let first = fetch_url(&cached_context(cache_dir.path()), &url, ContentFormat::Text)
+            .await
+            .unwrap();
+        let second = fetch_url(&cached_context(cache_dir.path()), &url, ContentFormat::Text)
+            .await
+            .unwrap();
+
+        assert_eq!(first.text, "Cached page");
+        assert_eq!(second.text, "Cached page");
+        assert_eq!(requests_to(&server, "/page").await.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_fetch_rechecks_domain_policy_for_cached_redirect_target() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/old"))
+            .respond_with(ResponseTemplate::new(301).insert_header("location", "/new"))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/new"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .insert_header("cache-control", "max-age=600")
+                    .set_body_raw("<html><body><p>Moved here</p></body></html>", "text/html"),
+            )
+            .mount(&server)
+            .await;
+        let cache_dir = tempfile::tempdir().unwrap();
+        let url = format!("{}/old", server.uri());
+        let policy = DomainPolicy {
+            deny: vec!["127.0.0.1/new".parse().unwrap()],
+            ..DomainPolicy::default()
+        };
+
+        let first = fetch_url(&cached_context(cache_dir.path()), &url, ContentFormat::Text)
+            .await
+            .unwrap();
+        let denied = fetch_url(
+            &cached_context(cache_dir.path()).with_domain_policy(policy),
+            &url,
+            ContentFormat::Text,
+        )
+        .await;
+
+        assert_eq!(first.text, "Moved here");
+        assert!(matches!(denied, Err(FetchError::DomainBlocked(_))));
+        assert_eq!(requests_to(&server, "/old").await.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_fetch_revalidates_stale_page_with_etag() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/page"))
+            .and(header("if-none-match", "\"v1\""))
+            .respond_with(ResponseTemplate::new(304))
+            .with_priority(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/page"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .insert_header("etag", "\"v1\"")
+                    .set_body_raw(
+                        "<html><body><p>Versioned page</p></body></html>",
+                        "text/html",
+                    ),
+            )
+            .mount(&server)
+            .await;
+        let cache_dir = tempfile::tempdir().unwrap();
+        let context = cached_context(cache_dir.path());
+        let url = format!("{}/page", server.uri());
+
+        fetch_url(&context, &url, ContentFormat::Text)
+            .await
+            .unwrap();
+        let revalidated = fetch_url(&context, &url, ContentFormat::Text)
+            .await
+            .unwrap();
+
+        assert_eq!(revalidated.text, "Versioned page");
+        let requests = requests_to(&server, "/page").await;
+        assert_eq!(requests.len(), 2);
+        assert_eq!(requests[1].headers["if-none-match"], "\"v1\"");
+    }
+
+    // --- ReqwestClient tests (against a local stand-in server) ---
+
+    #[tokio::test]
+    async fn test_reqwest_client_sends_configured_user_agent() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/page"))
+            .and(header("user-agent", "test-agent/1.0"))
+            .respond_with(ResponseTemplate::new(200).set_body_string("hello"))
+            .mount(&server)
+            .await;
+        let config = HttpConfig {
+            user_agent: "test-agent/1.0".to_string(),
+            ..HttpConfig::default()
+        };
+        let client = ReqwestClient::new(&config).unwrap();
+
+        let response = client.get(&format!("{}/page", server.uri())).await.unwrap();
+
+        assert_eq!(response.text(), "hello");
+    }
+
+    #[tokio::test]
+    async fn test_reqwest_client_times_out() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(200).set_delay(Duration::from_secs(5)))
+            .mount(&server)
+            .await;
+        let config = HttpConfig {
+            timeout: Duration::from_millis(100),
+            ..HttpConfig::default()
+        };
+        let client = ReqwestClient::new(&config).unwrap();
+
+        let result = client.get(&server.uri()).await;
+
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_reqwest_client_limits_redirects() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/loop"))
+            .respond_with(ResponseTemplate::new(302).insert_header("location", "/loop"))
+            .mount(&server)
+            .await;
+        let config = HttpConfig {
+            max_redirects: 3,
+            ..HttpConfig::default()
+        };
+        let client = ReqwestClient::new(&config).unwrap();
+
+        let result = client.get(&format!("{}/loop", server.uri())).await;
+
+        assert!(result.is_err());
+        assert_eq!(server.received_requests().await.unwrap().len(), 4);
+    }
+
+    #[tokio::test]
+    async fn test_reqwest_client_decompresses_gzip() {
+        // gzip-compressed "hello gzip"
+        const GZIPPED: &[u8] = &[
+            0x1f, 0x8b, 0x08, 0x00, 0x00, 0x00, 0x00, 0x00, 0x02, 0x03, 0xcb, 0x48, 0xcd, 0xc9,
+            0xc9, 0x57, 0x48, 0xaf, 0xca, 0x2c, 0x00, 0x00, 0x19, 0x6a, 0xd2, 0xdf, 0x0a, 0x00,
+            0x00, 0x00,
+        ];
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .insert_header("content-encoding", "gzip")
+                    .set_body_bytes(GZIPPED),
+            )
+            .mount(&server)
+            .await;
+        let client = ReqwestClient::new(&HttpConfig::default()).unwrap();
+
+        let response = client.get(&server.uri()).await.unwrap();
+
+        assert_eq!(response.text(), "hello gzip");
+        let requests = server.received_requests().await.unwrap();
+        let accept_encoding = requests[0].headers["accept-encoding"].to_str().unwrap();
+        assert!(accept_encoding.contains("gzip"));
+        assert!(accept_encoding.contains("br"));
+    }
+
+    #[tokio::test]
+    async fn test_reqwest_client_returns_status_final_url_and_headers() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/old"))
+            .respond_with(ResponseTemplate::new(301).insert_header("location", "/new"))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/new"))
+            .respond_with(
+                ResponseTemplate::new(404).set_body_raw("not here", "text/html; charset=utf-8"),
+            )
+            .mount(&server)
+            .await;
+        let client = ReqwestClient::new(&HttpConfig::default()).unwrap();
+
+        let response = client.get(&format!("{}/old", server.uri())).await.unwrap();
+
+        assert_eq!(response.status, StatusCode::NOT_FOUND);
+        assert_eq!(response.url, format!("{}/new", server.uri()));
+        assert_eq!(response.media_type(), Some("text/html".to_string()));
+        assert_eq!(response.body, b"not here");
+    }
+
+    #[tokio::test]
+    async fn test_reqwest_client_get_no_redirect_returns_redirect() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/old"))
+            .respond_with(ResponseTemplate::new(301).insert_header("location", "/new"))
+            .mount(&server)
+            .await;
+        let client = ReqwestClient::new(&HttpConfig::default()).unwrap();
+
+        let response = client
+            .get_no_redirect(&format!("{}/old", server.uri()))
+            .await
+            .unwrap();
+
+        assert_eq!(response.status, StatusCode::MOVED_PERMANENTLY);
+        assert_eq!(response.headers["location"], "/new");
+        assert_eq!(server.received_requests().await.unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_fetch_retries_service_unavailable() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/flaky"))
+            .respond_with(ResponseTemplate::new(503).insert_header("retry-after", "0"))
+            .up_to_n_times(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/flaky"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_raw("<html><body><p>Back up</p></body></html>", "text/html"),
+            )
+            .mount(&server)
+            .await;
+        let client = ReqwestClient::new(&HttpConfig::default()).unwrap();
+
+        let page = fetch_url(
+            &context(client),
+            &format!("{}/flaky", server.uri()),
+            ContentFormat::Text,
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(page.text, "Back up");
+        assert_eq!(requests_to(&server, "/flaky").await.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_reqwest_client_rejects_declared_oversized_body() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/big"))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_raw(vec![b'a'; 2048], "application/pdf"),
+            )
+            .mount(&server)
+            .await;
+        let config = HttpConfig {
+            size_limits: SizeLimits {
+                max_page_bytes: 4096,
+                max_pdf_bytes: 1024,
+            },
+            ..HttpConfig::default()
+        };
+        let client = ReqwestClient::new(&config).unwrap();
+
+        let err = client
+            .get(&format!("{}/big", server.uri()))
+            .await
+            .unwrap_err();
+
+        assert!(matches!(
+            FetchError::from(err),
+            FetchError::BodyTooLarge {
+                size: Some(2048),
+                limit: 1024,
+                ..
+            }
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_reqwest_client_reads_pages_up_to_page_limit() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/big"))
+            .respond_with(ResponseTemplate::new(200).set_body_raw(vec![b'a'; 2048], "text/html"))
+            .mount(&server)
+            .await;
+        let config = HttpConfig {
+            size_limits: SizeLimits {
+                max_page_bytes: 100,
+                max_pdf_bytes: 4096,
+            },
+            ..HttpConfig::default()
+        };
+        let client = ReqwestClient::new(&config).unwrap();
+        let url = format!("{}/big", server.uri());
+
+        let page = client.get(&url).await.unwrap();
+        let robots = client.get_no_redirect_limited(&url, 10).await.unwrap();
+
+        assert_eq!(page.body.len(), 100);
+        assert!(page.truncated);
+        assert_eq!(robots.body.len(), 10);
+        assert!(robots.truncated);
+    }
+
+    #[tokio::test]
+    async fn test_fetch_cuts_page_declaring_more_than_pdf_limit() {
+        let server = MockServer::start().await;
+        let html = format!("<html><body><p>{}</p></body></html>", "a".repeat(2048));
+        Mock::given(method("GET"))
+            .and(path("/big"))
+            .respond_with(ResponseTemplate::new(200).set_body_raw(html, "text/html"))
+            .mount(&server)
+            .await;
+        let limits = SizeLimits {
+            max_page_bytes: 100,
+            max_pdf_bytes: 1024,
+        };
+        let config = HttpConfig {
+            size_limits: limits,
+            ..HttpConfig::default()
+        };
+        let client = ReqwestClient::new(&config).unwrap();
+        let context = context(client).with_size_limits(limits);
+
+        let result = fetch_url(
+            &context,
+            &format!("{}/big", server.uri()),
+            ContentFormat::Text,
+        )
+        .await
+        .unwrap();
+
+        assert!(result.truncated);
+        assert!(result.text.len() < 100);
+        assert!(result.text.starts_with("aaa"));
+    }
+
+    /// Serve one endless response without Content-Length, as a misbehaving server would
+    async fn endless_server() -> String {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut request = [0; 1024];
+            let _ = socket.read(&mut request).await;
+            let header = "HTTP/1.1 200 OK\r\nContent-Type: text/plain\r\nConnection: close\r\n\r\n";
+            if socket.write_all(header.as_bytes()).await.is_err() {
+                return;
+            }
+            // Until the client hangs up
+            while socket.write_all(&[b'a'; 4096]).await.is_ok() {}
+        });
+        format!("http://{}/stream", address)
+    }
+
+    #[tokio::test]
+    async fn test_reqwest_client_cuts_endless_body_at_limit() {
+        let url = endless_server().await;
+        let config = HttpConfig {
+            size_limits: SizeLimits {
+                max_page_bytes: 100_000,
+                ..SizeLimits::default()
+            },
+            ..HttpConfig::default()
+        };
+        let client = ReqwestClient::new(&config).unwrap();
+
+        let response = client.get(&url).await.unwrap();
+
+        assert_eq!(response.body.len(), 100_000);
+        assert!(response.truncated);
+    }
+
+    // --- URL policy tests ---
+
+    #[tokio::test]
+    async fn test_fetch_refuses_domain_outside_allowlist() {
+        let mock_client = MockHttpClient::new().with_response(
+            "https://example.com/",
+            "<html><body><p>Hi</p></body></html>",
+        );
+        let policy = DomainPolicy {
+            allow: vec!["docs.rs".parse().unwrap()],
+            ..DomainPolicy::default()
+        };
+
+        let result = fetch_url(
+            &context(mock_client).with_domain_policy(policy),
+            "https://example.com/",
+            ContentFormat::Text,
+        )
+        .await;
+
+        assert!(matches!(
+            result.unwrap_err(),
+            FetchError::DomainBlocked(DomainBlocked::NotAllowed { .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_fetch_blocks_cloud_metadata_endpoint() {
+        let mock_client = MockHttpClient::new().with_response(
+            "http://169.254.169.254/latest/meta-data/",
+            "<html><body><p>secret</p></body></html>",
+        );
+
+        let result = fetch_url(
+            &context(mock_client),
+            "http://169.254.169.254/latest/meta-data/",
+            ContentFormat::Text,
+        )
+        .await;
+
+        let err = result.unwrap_err();
+        assert!(matches!(
+            err,
+            FetchError::UrlBlocked(UrlBlocked::PrivateAddress { .. })
+        ));
+        assert!(err.to_string().contains("private or reserved address"));
+    }
+
+    #[tokio::test]
+    async fn test_fetch_blocks_local_server_unless_allowlisted() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/admin"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_raw("<html><body><p>Admin panel</p></body></html>", "text/html"),
+            )
+            .mount(&server)
+            .await;
+        let client = ReqwestClient::new(&HttpConfig::default()).unwrap();
+        let context = FetchContext::new(client, PolitenessConfig::default());
+        let url = format!("{}/admin", server.uri());
+
+        let result = fetch_url(&context, &url, ContentFormat::Text).await;
+
+        assert!(matches!(result.unwrap_err(), FetchError::UrlBlocked(_)));
+        // Not even robots.txt was requested
+        assert!(server.received_requests().await.unwrap().is_empty());
+
+        let allowed =
+            context.with_url_policy(UrlPolicy::default().allow_host(&server.address().to_string()));
+        let page = fetch_url(&allowed, &url, ContentFormat::Text)
+            .await
+            .unwrap();
+        assert_eq!(page.text, "Admin panel");
+    }
+
+    #[tokio::test]
+    async fn test_fetch_rechecks_policy_after_redirect() {
+        let server = MockServer::start().await;
+        let internal = format!("http://localhost:{}/internal", server.address().port());
+        Mock::given(method("GET"))
+            .and(path("/go"))
+            .respond_with(ResponseTemplate::new(302).insert_header("location", internal.as_str()))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/internal"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_raw("<html><body><p>Internal</p></body></html>", "text/html"),
+            )
+            .mount(&server)
+            .await;
+        let client = ReqwestClient::new(&HttpConfig::default()).unwrap();
+
+        let result = fetch_url(
+            &context(client),
+            &format!("{}/go", server.uri()),
+            ContentFormat::Text,
+        )
+        .await;
+
+        // 127.0.0.1 is allowlisted, localhost is not
+        assert!(matches!(
+            result.unwrap_err(),
+            FetchError::UrlBlocked(UrlBlocked::PrivateAddress { host, .. }) if host == "localhost"
+        ));
+        assert!(requests_to(&server, "/internal").await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_reqwest_client_refuses_to_connect_to_private_host_names() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/internal"))
+            .respond_with(ResponseTemplate::new(200).set_body_raw("Internal", "text/plain"))
+            .mount(&server)
+            .await;
+        let url = format!("http://localhost:{}/internal", server.address().port());
+        let allowing = HttpConfig {
+            url_policy: Some(UrlPolicy::default().allow_host("localhost")),
+            ..HttpConfig::default()
+        };
+
+        // Even when the URL policy check is skipped, as a rebinding host would get past it
+        let refused = ReqwestClient::new(&HttpConfig::default())
+            .unwrap()
+            .get(&url)
+            .await;
+        let allowed = ReqwestClient::new(&allowing).unwrap().get(&url).await;
+
+        let err = refused.unwrap_err();
+        assert!(err.chain().any(|cause| matches!(
+            cause.downcast_ref::<UrlBlocked>(),
+            Some(UrlBlocked::PrivateConnection { .. })
+        )));
+        assert_eq!(allowed.unwrap().status, StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_fetch_refuses_redirect_to_denied_domain() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/go"))
+            .respond_with(
+                ResponseTemplate::new(302).insert_header("location", "https://paywalled.example/"),
+            )
+            .mount(&server)
+            .await;
+        let client = ReqwestClient::new(&HttpConfig::default()).unwrap();
+        let policy = DomainPolicy {
+            deny: vec!["*.paywalled.example".parse().unwrap()],
+            ..DomainPolicy::default()
+        };
+
+        let result = fetch_url(
+            &context(client).with_domain_policy(policy),
+            &format!("{}/go", server.uri()),
+            ContentFormat::Text,
+        )
+        .await;
+
+        let err = result.unwrap_err();
+        assert!(matches!(err, FetchError::DomainBlocked(_)));
+        assert_eq!(
+            err.to_string(),
+            "https://paywalled.example/ is blocked by the domain policy (denied by \"*.paywalled.example\")"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_fetch_follows_allowed_redirects() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/old"))
+            .respond_with(ResponseTemplate::new(301).insert_header("location", "/new"))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/new"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_raw("<html><body><p>Moved here</p></body></html>", "text/html"),
+            )
+            .mount(&server)
+            .await;
+        let client = ReqwestClient::new(&HttpConfig::default()).unwrap();
+
+        let page = fetch_url(
+            &context(client),
+            &format!("{}/old", server.uri()),
+            ContentFormat::Text,
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(page.url, format!("{}/new", server.uri()));
+        assert_eq!(page.text, "Moved here");
+    }
+
+    #[tokio::test]
+    async fn test_fetch_refuses_redirect_to_path_disallowed_by_robots_txt() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/robots.txt"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_raw("User-agent: *\nDisallow: /private/", "text/plain"),
+            )
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/public"))
+            .respond_with(ResponseTemplate::new(302).insert_header("location", "/private/page"))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/private/page"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_raw("<html><body><p>Private</p></body></html>", "text/html"),
+            )
+            .mount(&server)
+            .await;
+        let client = ReqwestClient::new(&HttpConfig::default()).unwrap();
+
+        let result = fetch_url(
+            &context(client),
+            &format!("{}/public", server.uri()),
+            ContentFormat::Text,
+        )
+        .await;
+
+        assert!(matches!(
+            result.unwrap_err(),
+            FetchError::RobotsDisallowed(url) if url == format!("{}/private/page", server.uri())
+        ));
+        assert!(requests_to(&server, "/private/page").await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_fetch_follows_configured_number_of_redirects() {
+        let server = MockServer::start().await;
+        for i in 0..3 {
+            Mock::given(method("GET"))
+                .and(path(format!("/r{}", i)))
+                .respond_with(
+                    ResponseTemplate::new(302).insert_header("location", format!("/r{}", i + 1)),
+                )
+                .mount(&server)
+                .await;
+        }
+        Mock::given(method("GET"))
+            .and(path("/r3"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_raw("<html><body><p>Arrived</p></body></html>", "text/html"),
+            )
+            .mount(&server)
+            .await;
+        let url = format!("{}/r0", server.uri());
+        let limited = |max_redirects| {
+            let config = HttpConfig {
+                max_redirects,
+                ..HttpConfig::default()
+            };
+            context(ReqwestClient::new(&config).unwrap())
+        };
+
+        let result = fetch_url(&limited(2), &url, ContentFormat::Text).await;
+        assert!(result
+            .unwrap_err()
+            .to_string()
+            .contains("Too many redirects"));
+        assert!(requests_to(&server, "/r3").await.is_empty());
+
+        let page = fetch_url(&limited(3), &url, ContentFormat::Text)
+            .await
+            .unwrap();
+        assert_eq!(page.text, "Arrived");
+    }
+
+    #[test]
+    fn test_reqwest_client_rejects_invalid_proxy() {
+        let config = HttpConfig {
+            proxy: Some("not a proxy url".to_string()),
+            ..HttpConfig::default()
+        };
+
+        assert!(ReqwestClient::new(&config).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_fetch_allowed_when_robots_txt_missing() {
+        // robots.txt returns 404 → everything is allowed → fetch proceeds
+        let mock_client = MockHttpClient::new().with_response(
+            "https://example.com/page",
+            "<html><body><p>Content</p></body></html>",
+        );
+
+        let result = fetch_url(
+            &context(mock_client),
+            "https://example.com/page",
+            ContentFormat::Text,
+        )
+        .await
+        .unwrap();
+
         assert_eq!(result.text, "Content");
     }
 }
@@ -0,0 +1,125 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use serde::Deserialize;
+
+use crate::collectors::domain_policy::DomainPolicy;
+use crate::collectors::search::SearchConfig;
+use crate::collectors::web::SizeLimits;
+
+/// Settings read from the config file (TOML)
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    /// Sites the agent may or may not use
+    #[serde(default)]
+    pub domains: DomainPolicy,
+    /// Which search backend to use
+    #[serde(default)]
+    pub search: SearchConfig,
+    /// Largest pages and PDFs the web tools read
+    #[serde(default)]
+    pub limits: SizeLimits,
+}
+
+impl Config {
+    /// `$XDG_CONFIG_HOME/cipherant/config.toml` (or the platform equivalent)
+    pub fn default_path() -> Option<PathBuf> {
+        dirs::config_dir().map(|dir| dir.join("cipherant").join("config.toml"))
+    }
+
+    /// Load the config file at `path`, or at the default path if there is one.
+    /// A missing default config file gives the default settings.
+    ///
+    /// # Errors
+    /// Returns an error if the file can't be read or isn't a valid config.
+    pub fn load(path: Option<&Path>) -> Result<Self> {
+        let path = match path {
+            Some(path) => path.to_path_buf(),
+            None => match Self::default_path() {
+                Some(path) if path.exists() => path,
+                _ => return Ok(Self::default()),
+            },
+        };
+        let content = std::fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read config file {}", path.display()))?;
+        Self::parse(&content).with_context(|| format!("Invalid config file {}", path.display()))
+    }
+
+    fn parse(content: &str) -> Result<Self> {
+        Ok(toml::from_str(content)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_domain_policy() {
+        let config = Config::parse(
+            r#"
+            [domains]
+            allow = ["*.rust-lang.org", "docs.rs"]
+            deny = ["blog.rust-lang.org", "example.com/private/"]
+            "#,
+        )
+        .unwrap();
+
+        assert!(config.domains.allows("https://doc.rust-lang.org/std/"));
+        assert!(!config.domains.allows("https://blog.rust-lang.org/"));
+        assert!(!config.domains.allows("https://example.org/"));
+    }
+
+    #[test]
+    fn test_parse_search_provider() {
+        let config = Config::parse(
+            r#"
+            [search]
+            provider = "searxng"
+            url = "http://localhost:8888"
+            "#,
+        )
+        .unwrap();
+        assert_eq!(
+            config.search,
+            SearchConfig::Searxng {
+                url: "http://localhost:8888".to_string()
+            }
+        );
+
+        let config = Config::parse("[search]\nprovider = \"duckduckgo\"").unwrap();
+        assert_eq!(config.search, SearchConfig::DuckDuckGo);
+        assert!(Config::parse("[search]\nprovider = \"bing\"").is_err());
+    }
+
+    #[test]
+    fn test_parse_size_limits() {
+        let config = Config::parse("[limits]\nmax_pdf_bytes = 52428800").unwrap();
+
+        assert_eq!(config.limits.max_pdf_bytes, 50 * 1024 * 1024);
+        assert_eq!(
+            config.limits.max_page_bytes,
+            SizeLimits::default().max_page_bytes
+        );
+        assert!(Config::parse("[limits]\nmax_bytes = 1024").is_err());
+    }
+
+    #[test]
+    fn test_empty_config_is_default() {
+        assert_eq!(Config::parse("").unwrap(), Config::default());
+    }
+
+    #[test]
+    fn test_invalid_config_is_rejected() {
+        assert!(Config::parse("[domains]\ndeny = [\"exa*mple.com\"]").is_err());
+        assert!(Config::parse("[domains]\nblock = [\"example.com\"]").is_err());
+    }
+
+    #[test]
+    fn test_load_missing_explicit_file_fails() {
+        let dir = tempfile::tempdir().unwrap();
+
+        assert!(Config::load(Some(&dir.path().join("missing.toml"))).is_err());
+    }
+}
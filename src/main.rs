@@ -6,9 +6,10 @@ use rig::completion::{CompletionModel, GetTokenUsage, Prompt};
 use std::env;
 
 use cipherant::agent::{
-    create_gemini_agent, create_ollama_agent, create_openai_agent, default_model,
+    create_gemini_agent, create_ollama_agent, create_openai_agent, default_model, AgentOptions,
 };
 use cipherant::cli::{render_markdown, run_interactive, Cli};
+use cipherant::config::Config;
 
 #[tokio::main]
 async fn main() {
@@ -18,25 +19,41 @@ async fn main() {
     env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("info")).init();
 
     let args = Cli::parse();
+    let config = match Config::load(args.config.as_deref()) {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!("Error: {:#}", e);
+            std::process::exit(1);
+        }
+    };
 
     let provider = env::var("LLM_PROVIDER").unwrap_or_else(|_| "ollama".to_string());
     let model = env::var("LLM_MODEL").unwrap_or_else(|_| default_model(&provider).to_string());
+    let options = AgentOptions {
+        cache_mode: args.cache_mode(),
+        opt_out_policy: args.opt_out,
+        allowed_local_hosts: args.allowed_local_hosts.clone(),
+        domain_policy: args.domain_policy(config.domains),
+        retry_policy: args.retry_policy(),
+        search: config.search,
+        size_limits: config.limits,
+    };
 
     match provider.as_str() {
         "openai" => {
             let api_key =
                 env::var("OPENAI_API_KEY").expect("OPENAI_API_KEY required for OpenAI provider");
-            let agent = create_openai_agent(&api_key, &model);
+            let agent = create_openai_agent(&api_key, &model, &options);
             run_with_agent(agent, &args).await;
         }
         "gemini" => {
             let api_key =
                 env::var("GEMINI_API_KEY").expect("GEMINI_API_KEY required for Gemini provider");
-            let agent = create_gemini_agent(&api_key, &model);
+            let agent = create_gemini_agent(&api_key, &model, &options);
             run_with_agent(agent, &args).await;
         }
         _ => {
-            let agent = create_ollama_agent(&model);
+            let agent = create_ollama_agent(&model, &options);
             run_with_agent(agent, &args).await;
         }
     }